@@ -8,15 +8,20 @@ pub use self::{
     types::*,
 };
 
+use chashmap::CHashMap;
 use futures::channel::*;
+use futures::future::{AbortHandle, Abortable};
 use futures::prelude::*;
-use log::error;
+use log::{error, warn};
 use std::sync::Arc;
 
+const CANCEL_REQUEST_METHOD: &str = "$/cancelRequest";
+
 pub struct MessageHandler<S, C> {
     pub server: Arc<S>,
     pub client: Arc<C>,
     pub output: mpsc::Sender<String>,
+    pending_requests: Arc<CHashMap<Id, AbortHandle>>,
 }
 
 impl<S, C> MessageHandler<S, C>
@@ -24,15 +29,40 @@ where
     S: RequestHandler + Middleware + Send + Sync + 'static,
     C: ResponseHandler + Send + Sync + 'static,
 {
+    pub fn new(server: Arc<S>, client: Arc<C>, output: mpsc::Sender<String>) -> Self {
+        Self {
+            server,
+            client,
+            output,
+            pending_requests: Arc::new(CHashMap::new()),
+        }
+    }
+
     pub async fn handle(&mut self, json: &str) {
         self.server.before_message().await;
 
         match serde_json::from_str(json).map_err(|_| Error::parse_error()) {
             Ok(Message::Request(request)) => {
                 let server = Arc::clone(&self.server);
+                let pending_requests = Arc::clone(&self.pending_requests);
                 let mut output = self.output.clone();
+
+                let id = request.id.clone();
+                let (abort_handle, abort_registration) = AbortHandle::new_pair();
+                self.pending_requests.insert(id.clone(), abort_handle);
+
                 tokio::spawn(async move {
-                    let response = server.handle_request(request).await;
+                    let response =
+                        match Abortable::new(server.handle_request(request), abort_registration)
+                            .await
+                        {
+                            Ok(response) => response,
+                            Err(_) => {
+                                Response::error(Error::request_cancelled_error(), Some(id.clone()))
+                            }
+                        };
+                    pending_requests.remove(&id);
+
                     if let Some(error) = response.error.as_ref() {
                         error!("{:?}", error);
                     }
@@ -42,6 +72,10 @@ where
                 });
             }
             Ok(Message::Notification(notification)) => {
+                if notification.method == CANCEL_REQUEST_METHOD {
+                    self.cancel_request(&notification);
+                }
+
                 self.server.handle_notification(notification).await;
                 self.after_message();
             }
@@ -58,6 +92,17 @@ where
         };
     }
 
+    fn cancel_request(&self, notification: &Notification) {
+        match serde_json::from_value::<CancelParams>(notification.params.clone()) {
+            Ok(params) => {
+                if let Some(abort_handle) = self.pending_requests.remove(&params.id) {
+                    abort_handle.abort();
+                }
+            }
+            Err(_) => warn!("{}", Error::deserialize_error().message),
+        }
+    }
+
     fn after_message(&self) {
         let server = Arc::clone(&self.server);
         tokio::spawn(async move {
@@ -65,3 +110,62 @@ where
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_boxed::boxed;
+    use serde_json::json;
+
+    struct PendingServer;
+
+    impl RequestHandler for PendingServer {
+        #[boxed]
+        async fn handle_request(&self, _request: Request) -> Response {
+            future::pending().await
+        }
+
+        #[boxed]
+        async fn handle_notification(&self, _notification: Notification) {}
+    }
+
+    impl Middleware for PendingServer {
+        #[boxed]
+        async fn before_message(&self) {}
+
+        #[boxed]
+        async fn after_message(&self) {}
+    }
+
+    struct EmptyClient;
+
+    impl ResponseHandler for EmptyClient {
+        #[boxed]
+        async fn handle(&self, _response: Response) {}
+    }
+
+    #[tokio::test]
+    async fn cancel_request() {
+        let (output_tx, mut output_rx) = mpsc::channel(1);
+        let mut handler =
+            MessageHandler::new(Arc::new(PendingServer), Arc::new(EmptyClient), output_tx);
+
+        let request = Request::new("foo".to_owned(), json!(()), Id::Number(1));
+        handler
+            .handle(&serde_json::to_string(&request).unwrap())
+            .await;
+
+        let notification = Notification::new(
+            CANCEL_REQUEST_METHOD.to_owned(),
+            json!(CancelParams { id: Id::Number(1) }),
+        );
+        handler
+            .handle(&serde_json::to_string(&notification).unwrap())
+            .await;
+
+        let response: serde_json::Value =
+            serde_json::from_str(&output_rx.next().await.unwrap()).unwrap();
+        let expected = Response::error(Error::request_cancelled_error(), Some(Id::Number(1)));
+        assert_eq!(response, json!(expected));
+    }
+}
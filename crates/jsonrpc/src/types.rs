@@ -64,6 +64,14 @@ impl Error {
             data: serde_json::Value::Null,
         }
     }
+
+    pub fn request_cancelled_error() -> Self {
+        Self {
+            code: ErrorCode::RequestCancelled,
+            message: "Request cancelled".to_owned(),
+            data: serde_json::Value::Null,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -135,6 +143,11 @@ impl Notification {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CancelParams {
+    pub id: Id,
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Message {
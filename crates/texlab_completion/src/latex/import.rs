@@ -53,6 +53,7 @@ where
 
     combinators::argument(request, parameters, |context| {
         async move {
+            request.distribution.wait_until_loaded().await;
             let resolver = request.distribution.resolver().await;
             COMPONENT_DATABASE
                 .components
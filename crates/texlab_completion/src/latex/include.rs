@@ -31,13 +31,15 @@ impl FeatureProvider for LatexIncludeCompletionProvider {
                 let mut items = Vec::new();
                 let path_word = command.extract_word(index);
                 let name_range = match path_word {
-                    Some(path_word) => Range::new_simple(
-                        path_word.start().line,
-                        path_word.end().character
-                            - path_word.text().split('/').last().unwrap().chars().count() as u64,
-                        path_word.end().line,
-                        path_word.end().character,
-                    ),
+                    Some(path_word) => {
+                        let name = path_word.text().split('/').last().unwrap();
+                        Range::new_simple(
+                            path_word.start().line,
+                            path_word.end().character - name.encode_utf16().count() as u64,
+                            path_word.end().line,
+                            path_word.end().character,
+                        )
+                    }
                     None => Range::new(position, position),
                 };
                 let directory = current_directory(&request, &command);
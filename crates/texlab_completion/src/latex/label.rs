@@ -25,32 +25,38 @@ impl FeatureProvider for LatexLabelCompletionProvider {
             async move {
                 let options = &request.options;
                 let source = Self::find_source(&context);
+                let outline = Outline::analyze(&request.view, options);
                 let mut items = Vec::new();
                 for document in request.related_documents() {
+                    let tree = match &document.tree {
+                        SyntaxTree::Latex(tree) => tree,
+                        SyntaxTree::Bibtex(_) => continue,
+                    };
+
+                    if tree.structure.labels.is_empty() {
+                        continue;
+                    }
+
                     let workspace = Arc::clone(&request.view.workspace);
                     let view = DocumentView::new(workspace, Arc::clone(&document), options);
-                    let outline = Outline::analyze(&view, options);
-
-                    if let SyntaxTree::Latex(tree) = &document.tree {
-                        for label in tree
-                            .structure
-                            .labels
-                            .iter()
-                            .filter(|label| label.kind == LatexLabelKind::Definition)
-                            .filter(|label| Self::is_included(tree, label, source))
-                        {
-                            let outline_context = OutlineContext::parse(&view, &label, &outline);
-                            for name in label.names() {
-                                let text = name.text().to_owned();
-                                let text_edit = TextEdit::new(context.range, text.clone());
-                                let item = factory::label(
-                                    request,
-                                    text,
-                                    text_edit,
-                                    outline_context.as_ref(),
-                                );
-                                items.push(item);
-                            }
+                    for label in tree
+                        .structure
+                        .labels
+                        .iter()
+                        .filter(|label| label.kind == LatexLabelKind::Definition)
+                        .filter(|label| Self::is_included(tree, label, source))
+                    {
+                        let outline_context = OutlineContext::parse(&view, &label, &outline);
+                        for name in label.names() {
+                            let text = name.text().to_owned();
+                            let text_edit = TextEdit::new(context.range, text.clone());
+                            let item = factory::label(
+                                request,
+                                text,
+                                text_edit,
+                                outline_context.as_ref(),
+                            );
+                            items.push(item);
                         }
                     }
                 }
@@ -12,13 +12,16 @@ use self::miktex::Miktex;
 use self::tectonic::Tectonic;
 use self::texlive::Texlive;
 use futures_boxed::boxed;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
 use tokio::process::Command;
+use tokio::sync::watch;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum DistributionKind {
     Texlive,
     Miktex,
@@ -55,7 +58,8 @@ impl DistributionKind {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Resolver {
     pub files_by_name: HashMap<String, PathBuf>,
 }
@@ -66,6 +70,35 @@ impl Resolver {
     }
 }
 
+/// Signals that the file database of a distribution is available.
+#[derive(Debug)]
+pub struct LoadSignal {
+    sender: watch::Sender<bool>,
+    receiver: watch::Receiver<bool>,
+}
+
+impl Default for LoadSignal {
+    fn default() -> Self {
+        let (sender, receiver) = watch::channel(false);
+        Self { sender, receiver }
+    }
+}
+
+impl LoadSignal {
+    pub fn notify(&self) {
+        let _ = self.sender.broadcast(true);
+    }
+
+    pub async fn wait(&self) {
+        let mut receiver = self.receiver.clone();
+        while !*receiver.borrow() {
+            if receiver.recv().await.is_none() {
+                break;
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum LoadError {
     KpsewhichNotFound,
@@ -97,8 +130,17 @@ pub trait Distribution: Send + Sync {
     #[boxed]
     async fn load(&self) -> Result<(), LoadError>;
 
+    /// Replaces the file database with a previously loaded one, e.g. from the cache.
+    #[boxed]
+    async fn restore(&self, _resolver: Arc<Resolver>) {}
+
     #[boxed]
     async fn resolver(&self) -> Arc<Resolver>;
+
+    /// Waits until the file database has been loaded or restored.
+    /// Loading the database also counts as done if it failed.
+    #[boxed]
+    async fn wait_until_loaded(&self) {}
 }
 
 impl dyn Distribution {
@@ -1,19 +1,19 @@
 use super::compile::*;
 use super::kpsewhich;
-use super::{Distribution, DistributionKind, LoadError, Resolver};
+use super::{Distribution, DistributionKind, LoadError, LoadSignal, Resolver};
 use byteorder::{LittleEndian, ReadBytesExt};
 use futures::lock::Mutex;
 use futures_boxed::boxed;
 use std::ffi::OsStr;
 use std::fs;
 use std::io::{self, Cursor};
-use std::mem;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 #[derive(Debug, Default)]
 pub struct Miktex {
     resolver: Mutex<Arc<Resolver>>,
+    loaded: LoadSignal,
 }
 
 impl Miktex {
@@ -36,10 +36,23 @@ impl Distribution for Miktex {
 
     #[boxed]
     async fn load(&self) -> Result<(), LoadError> {
-        let resolver = Arc::new(kpsewhich::parse_database(read_database).await?);
+        let result = match kpsewhich::parse_database(read_database).await {
+            Ok(resolver) => {
+                let mut resolver_guard = self.resolver.lock().await;
+                *resolver_guard = Arc::new(resolver);
+                Ok(())
+            }
+            Err(why) => Err(why),
+        };
+        self.loaded.notify();
+        result
+    }
+
+    #[boxed]
+    async fn restore(&self, resolver: Arc<Resolver>) {
         let mut resolver_guard = self.resolver.lock().await;
-        mem::replace(&mut *resolver_guard, resolver);
-        Ok(())
+        *resolver_guard = resolver;
+        self.loaded.notify();
     }
 
     #[boxed]
@@ -47,6 +60,11 @@ impl Distribution for Miktex {
         let resolver = self.resolver.lock().await;
         Arc::clone(&resolver)
     }
+
+    #[boxed]
+    async fn wait_until_loaded(&self) {
+        self.loaded.wait().await;
+    }
 }
 
 const DATABASE_PATH: &str = "miktex/data/le";
@@ -1,11 +1,10 @@
 use super::compile::*;
 use super::kpsewhich;
-use super::{Distribution, DistributionKind, LoadError, Resolver};
+use super::{Distribution, DistributionKind, LoadError, LoadSignal, Resolver};
 use futures::lock::Mutex;
 use futures_boxed::boxed;
 use std::fs;
 use std::io;
-use std::mem;
 use std::path::{Path, PathBuf};
 use std::str::Lines;
 use std::sync::Arc;
@@ -13,6 +12,7 @@ use std::sync::Arc;
 #[derive(Debug, Default)]
 pub struct Texlive {
     resolver: Mutex<Arc<Resolver>>,
+    loaded: LoadSignal,
 }
 
 impl Texlive {
@@ -35,10 +35,23 @@ impl Distribution for Texlive {
 
     #[boxed]
     async fn load(&self) -> Result<(), LoadError> {
-        let resolver = Arc::new(kpsewhich::parse_database(read_database).await?);
+        let result = match kpsewhich::parse_database(read_database).await {
+            Ok(resolver) => {
+                let mut resolver_guard = self.resolver.lock().await;
+                *resolver_guard = Arc::new(resolver);
+                Ok(())
+            }
+            Err(why) => Err(why),
+        };
+        self.loaded.notify();
+        result
+    }
+
+    #[boxed]
+    async fn restore(&self, resolver: Arc<Resolver>) {
         let mut resolver_guard = self.resolver.lock().await;
-        mem::replace(&mut *resolver_guard, resolver);
-        Ok(())
+        *resolver_guard = resolver;
+        self.loaded.notify();
     }
 
     #[boxed]
@@ -46,6 +59,11 @@ impl Distribution for Texlive {
         let resolver = self.resolver.lock().await;
         Arc::clone(&resolver)
     }
+
+    #[boxed]
+    async fn wait_until_loaded(&self) {
+        self.loaded.wait().await;
+    }
 }
 
 const DATABASE_PATH: &'static str = "ls-R";
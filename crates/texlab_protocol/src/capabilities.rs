@@ -12,6 +12,8 @@ pub trait ClientCapabilitiesExt {
     fn has_pull_configuration_support(&self) -> bool;
 
     fn has_push_configuration_support(&self) -> bool;
+
+    fn has_watched_files_support(&self) -> bool;
 }
 
 impl ClientCapabilitiesExt for ClientCapabilities {
@@ -55,6 +57,14 @@ impl ClientCapabilitiesExt for ClientCapabilities {
             .and_then(|cap| cap.dynamic_registration)
             == Some(true)
     }
+
+    fn has_watched_files_support(&self) -> bool {
+        self.workspace
+            .as_ref()
+            .and_then(|cap| cap.did_change_watched_files)
+            .and_then(|cap| cap.dynamic_registration)
+            == Some(true)
+    }
 }
 
 #[cfg(test)]
@@ -141,4 +151,24 @@ mod tests {
         let capabilities = ClientCapabilities::default();
         assert!(!capabilities.has_hover_markdown_support());
     }
+
+    #[test]
+    fn has_watched_files_support_true() {
+        let capabilities = ClientCapabilities {
+            workspace: Some(WorkspaceClientCapabilities {
+                did_change_watched_files: Some(GenericCapability {
+                    dynamic_registration: Some(true),
+                }),
+                ..WorkspaceClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        };
+        assert!(capabilities.has_watched_files_support());
+    }
+
+    #[test]
+    fn has_watched_files_support_false() {
+        let capabilities = ClientCapabilities::default();
+        assert!(!capabilities.has_watched_files_support());
+    }
 }
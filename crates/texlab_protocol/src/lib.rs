@@ -57,3 +57,17 @@ pub enum BuildStatus {
 pub struct BuildResult {
     pub status: BuildStatus,
 }
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Todo {
+    pub location: Location,
+    pub tag: String,
+    pub message: String,
+}
@@ -1,5 +1,14 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use log::warn;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// The most recently compiled exclude patterns.
+/// Only one list is kept because the patterns rarely change.
+static EXCLUDE_GLOB_SET: Lazy<Mutex<Option<(Vec<String>, Arc<GlobSet>)>>> =
+    Lazy::new(Mutex::default);
 
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -7,6 +16,12 @@ pub struct BibtexFormattingOptions {
     pub line_length: Option<i32>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatexFormattingOptions {
+    pub line_length: Option<i32>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 pub struct LatexForwardSearchOptions {
     pub executable: Option<String>,
@@ -62,13 +77,32 @@ impl LatexBuildOptions {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatexTodoOptions {
+    pub tags: Option<Vec<String>>,
+}
+
+impl LatexTodoOptions {
+    pub fn tags(&self) -> Vec<String> {
+        self.tags
+            .as_ref()
+            .map(Clone::clone)
+            .unwrap_or_else(|| vec!["TODO".to_owned(), "FIXME".to_owned()])
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LatexOptions {
     pub forward_search: Option<LatexForwardSearchOptions>,
     pub lint: Option<LatexLintOptions>,
     pub build: Option<LatexBuildOptions>,
+    pub formatting: Option<LatexFormattingOptions>,
     pub root_directory: Option<PathBuf>,
+    pub exclude: Option<Vec<String>>,
+    pub todo: Option<LatexTodoOptions>,
+    pub verbatim_environments: Option<Vec<String>>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
@@ -115,4 +149,86 @@ impl Options {
             })
             .or_else(|| tex_path.parent().map(|path| path.join(&name)))
     }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let patterns = match self.latex.as_ref().and_then(|latex| latex.exclude.as_ref()) {
+            Some(patterns) => patterns,
+            None => return false,
+        };
+
+        let set = {
+            let mut cache = EXCLUDE_GLOB_SET.lock().unwrap();
+            match &*cache {
+                Some((cached_patterns, set)) if cached_patterns == patterns => Arc::clone(set),
+                _ => {
+                    let set = Arc::new(compile_exclude_patterns(patterns));
+                    *cache = Some((patterns.clone(), Arc::clone(&set)));
+                    set
+                }
+            }
+        };
+        set.is_match(path)
+    }
+}
+
+fn compile_exclude_patterns(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(why) => warn!("Invalid exclude pattern ({}): {}", why, pattern),
+        }
+    }
+
+    builder.build().unwrap_or_else(|why| {
+        warn!("Unable to compile the exclude patterns: {}", why);
+        GlobSet::empty()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exclude(patterns: &[&str]) -> Options {
+        Options {
+            latex: Some(LatexOptions {
+                exclude: Some(
+                    patterns
+                        .iter()
+                        .map(|pattern| (*pattern).to_owned())
+                        .collect(),
+                ),
+                ..LatexOptions::default()
+            }),
+            bibtex: None,
+        }
+    }
+
+    #[test]
+    fn is_excluded_match() {
+        let options = exclude(&["**/build/**", "*.bak.tex"]);
+        assert!(options.is_excluded(Path::new("/foo/build/bar.tex")));
+        assert!(options.is_excluded(Path::new("/foo/bar.bak.tex")));
+    }
+
+    #[test]
+    fn is_excluded_no_match() {
+        let options = exclude(&["**/build/**"]);
+        assert!(!options.is_excluded(Path::new("/foo/bar.tex")));
+    }
+
+    #[test]
+    fn is_excluded_invalid_pattern() {
+        let options = exclude(&["[", "**/build/**"]);
+        assert!(options.is_excluded(Path::new("/foo/build/bar.tex")));
+        assert!(!options.is_excluded(Path::new("/foo/bar.tex")));
+    }
+
+    #[test]
+    fn is_excluded_default() {
+        assert!(!Options::default().is_excluded(Path::new("/foo/bar.tex")));
+    }
 }
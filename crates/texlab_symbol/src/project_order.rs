@@ -48,7 +48,7 @@ impl ProjectOrdering {
     fn connected_components(workspace: &Workspace, options: &Options) -> Vec<Vec<Arc<Document>>> {
         let mut components = Vec::new();
         let mut visited = HashSet::new();
-        for root in &workspace.documents {
+        for root in workspace.documents() {
             if !visited.insert(root.uri.clone()) {
                 continue;
             }
@@ -1,7 +1,8 @@
 use crate::text::{Span, SyntaxNode};
+use serde::{Deserialize, Serialize};
 use texlab_protocol::{Range, RangeExt};
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum BibtexTokenKind {
     PreambleKind,
     StringKind,
@@ -18,7 +19,7 @@ pub enum BibtexTokenKind {
     EndParen,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexToken {
     pub span: Span,
     pub kind: BibtexTokenKind,
@@ -40,7 +41,7 @@ impl SyntaxNode for BibtexToken {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexRoot {
     pub children: Vec<BibtexDeclaration>,
 }
@@ -64,7 +65,7 @@ impl SyntaxNode for BibtexRoot {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum BibtexDeclaration {
     Comment(Box<BibtexComment>),
     Preamble(Box<BibtexPreamble>),
@@ -94,7 +95,7 @@ impl SyntaxNode for BibtexDeclaration {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexComment {
     pub range: Range,
     pub token: BibtexToken,
@@ -115,7 +116,7 @@ impl SyntaxNode for BibtexComment {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexPreamble {
     pub range: Range,
     pub ty: BibtexToken,
@@ -156,7 +157,7 @@ impl SyntaxNode for BibtexPreamble {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexString {
     pub range: Range,
     pub ty: BibtexToken,
@@ -208,7 +209,7 @@ impl SyntaxNode for BibtexString {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexEntry {
     pub range: Range,
     pub ty: BibtexToken,
@@ -270,7 +271,7 @@ impl SyntaxNode for BibtexEntry {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexField {
     pub range: Range,
     pub name: BibtexToken,
@@ -312,7 +313,7 @@ impl SyntaxNode for BibtexField {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum BibtexContent {
     Word(BibtexWord),
     Command(BibtexCommand),
@@ -345,7 +346,7 @@ impl SyntaxNode for BibtexContent {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexWord {
     pub range: Range,
     pub token: BibtexToken,
@@ -366,7 +367,7 @@ impl SyntaxNode for BibtexWord {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexCommand {
     pub range: Range,
     pub token: BibtexToken,
@@ -387,7 +388,7 @@ impl SyntaxNode for BibtexCommand {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexQuotedContent {
     pub range: Range,
     pub left: BibtexToken,
@@ -424,7 +425,7 @@ impl SyntaxNode for BibtexQuotedContent {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexBracedContent {
     pub range: Range,
     pub left: BibtexToken,
@@ -461,7 +462,7 @@ impl SyntaxNode for BibtexBracedContent {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BibtexConcat {
     pub range: Range,
     pub left: BibtexContent,
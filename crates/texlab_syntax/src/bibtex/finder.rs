@@ -1,6 +1,6 @@
 use super::ast::*;
 use crate::text::SyntaxNode;
-use texlab_protocol::{Position, RangeExt};
+use texlab_protocol::{Position, Range, RangeExt};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum BibtexNode<'a> {
@@ -17,6 +17,24 @@ pub enum BibtexNode<'a> {
     Concat(&'a BibtexConcat),
 }
 
+impl<'a> SyntaxNode for BibtexNode<'a> {
+    fn range(&self) -> Range {
+        match self {
+            BibtexNode::Root(root) => root.range(),
+            BibtexNode::Preamble(preamble) => preamble.range,
+            BibtexNode::String(string) => string.range,
+            BibtexNode::Entry(entry) => entry.range,
+            BibtexNode::Comment(comment) => comment.range,
+            BibtexNode::Field(field) => field.range,
+            BibtexNode::Word(word) => word.range,
+            BibtexNode::Command(command) => command.range,
+            BibtexNode::QuotedContent(content) => content.range,
+            BibtexNode::BracedContent(content) => content.range,
+            BibtexNode::Concat(concat) => concat.range,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BibtexFinder<'a> {
     pub position: Position,
@@ -111,7 +111,7 @@ mod tests {
         kind: BibtexTokenKind,
     ) {
         let start = Position::new(line, character);
-        let end = Position::new(line, character + text.chars().count() as u64);
+        let end = Position::new(line, character + text.encode_utf16().count() as u64);
         let range = Range::new(start, end);
         let span = Span::new(range, text.to_owned());
         let token = BibtexToken::new(span, kind);
@@ -1,9 +1,10 @@
 use crate::text::{Span, SyntaxNode};
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use texlab_protocol::{Range, RangeExt};
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum LatexTokenKind {
     Word,
     Command,
@@ -13,9 +14,11 @@ pub enum LatexTokenKind {
     EndGroup,
     BeginOptions,
     EndOptions,
+    Comment,
+    Verbatim,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct LatexToken {
     pub span: Span,
     pub kind: LatexTokenKind,
@@ -37,7 +40,7 @@ impl SyntaxNode for LatexToken {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 pub struct LatexRoot {
     pub children: Vec<LatexContent>,
 }
@@ -61,13 +64,14 @@ impl SyntaxNode for LatexRoot {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum LatexContent {
     Group(Arc<LatexGroup>),
     Command(Arc<LatexCommand>),
     Text(Arc<LatexText>),
     Comma(Arc<LatexComma>),
     Math(Arc<LatexMath>),
+    Verbatim(Arc<LatexVerbatim>),
 }
 
 impl LatexContent {
@@ -78,6 +82,7 @@ impl LatexContent {
             LatexContent::Text(text) => visitor.visit_text(Arc::clone(&text)),
             LatexContent::Comma(comma) => visitor.visit_comma(Arc::clone(&comma)),
             LatexContent::Math(math) => visitor.visit_math(Arc::clone(&math)),
+            LatexContent::Verbatim(verbatim) => visitor.visit_verbatim(Arc::clone(&verbatim)),
         }
     }
 }
@@ -90,17 +95,18 @@ impl SyntaxNode for LatexContent {
             LatexContent::Text(text) => text.range(),
             LatexContent::Comma(comma) => comma.range(),
             LatexContent::Math(math) => math.range(),
+            LatexContent::Verbatim(verbatim) => verbatim.range(),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum LatexGroupKind {
     Group,
     Options,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct LatexGroup {
     pub range: Range,
     pub left: LatexToken,
@@ -140,7 +146,7 @@ impl SyntaxNode for LatexGroup {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct LatexCommand {
     pub range: Range,
     pub name: LatexToken,
@@ -231,7 +237,10 @@ impl LatexCommand {
         for node in &self.args[index].children {
             match node {
                 LatexContent::Text(_) | LatexContent::Comma(_) => (),
-                LatexContent::Command(_) | LatexContent::Group(_) | LatexContent::Math(_) => {
+                LatexContent::Command(_)
+                | LatexContent::Group(_)
+                | LatexContent::Math(_)
+                | LatexContent::Verbatim(_) => {
                     return false;
                 }
             }
@@ -246,7 +255,7 @@ impl SyntaxNode for LatexCommand {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct LatexText {
     pub range: Range,
     pub words: Vec<LatexToken>,
@@ -267,7 +276,7 @@ impl SyntaxNode for LatexText {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct LatexComma {
     pub token: LatexToken,
 }
@@ -284,7 +293,7 @@ impl SyntaxNode for LatexComma {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct LatexMath {
     pub token: LatexToken,
 }
@@ -301,6 +310,24 @@ impl SyntaxNode for LatexMath {
     }
 }
 
+/// The body of a verbatim environment or the argument of an inline verbatim command.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct LatexVerbatim {
+    pub token: LatexToken,
+}
+
+impl LatexVerbatim {
+    pub fn new(token: LatexToken) -> Self {
+        Self { token }
+    }
+}
+
+impl SyntaxNode for LatexVerbatim {
+    fn range(&self) -> Range {
+        self.token.range()
+    }
+}
+
 pub trait LatexVisitor {
     fn visit_root(&mut self, root: Arc<LatexRoot>);
 
@@ -313,6 +340,8 @@ pub trait LatexVisitor {
     fn visit_comma(&mut self, comma: Arc<LatexComma>);
 
     fn visit_math(&mut self, math: Arc<LatexMath>);
+
+    fn visit_verbatim(&mut self, verbatim: Arc<LatexVerbatim>);
 }
 
 pub struct LatexWalker;
@@ -341,4 +370,6 @@ impl LatexWalker {
     pub fn walk_comma<T: LatexVisitor>(_visitor: &mut T, _comma: Arc<LatexComma>) {}
 
     pub fn walk_math<T: LatexVisitor>(_visitor: &mut T, _math: Arc<LatexMath>) {}
+
+    pub fn walk_verbatim<T: LatexVisitor>(_visitor: &mut T, _verbatim: Arc<LatexVerbatim>) {}
 }
@@ -1,7 +1,7 @@
 use super::ast::*;
 use crate::text::SyntaxNode;
 use std::sync::Arc;
-use texlab_protocol::{Position, RangeExt};
+use texlab_protocol::{Position, Range, RangeExt};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum LatexNode {
@@ -13,6 +13,19 @@ pub enum LatexNode {
     Math(Arc<LatexMath>),
 }
 
+impl SyntaxNode for LatexNode {
+    fn range(&self) -> Range {
+        match self {
+            LatexNode::Root(root) => root.range(),
+            LatexNode::Group(group) => group.range(),
+            LatexNode::Command(command) => command.range(),
+            LatexNode::Text(text) => text.range(),
+            LatexNode::Comma(comma) => comma.range(),
+            LatexNode::Math(math) => math.range(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct LatexFinder {
     pub position: Position,
@@ -70,4 +83,8 @@ impl LatexVisitor for LatexFinder {
             LatexWalker::walk_math(self, math);
         }
     }
+
+    fn visit_verbatim(&mut self, verbatim: Arc<LatexVerbatim>) {
+        LatexWalker::walk_verbatim(self, verbatim);
+    }
 }
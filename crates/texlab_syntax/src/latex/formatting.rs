@@ -0,0 +1,616 @@
+use super::ast::*;
+use super::lexer::VERBATIM_ENVIRONMENTS;
+use super::{LatexEnvironment, LatexSyntaxTree};
+use crate::text::{offset_at, SyntaxNode};
+use texlab_protocol::{LatexFormattingOptions, Position, Range, RangeExt};
+
+const ALIGNMENT_ENVIRONMENTS: &[&str] = &["tabular", "tabular*", "tabularx", "longtable"];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LatexFormattingParams {
+    pub tab_size: usize,
+    pub insert_spaces: bool,
+    pub options: LatexFormattingOptions,
+    pub verbatim_environments: Vec<String>,
+}
+
+impl LatexFormattingParams {
+    pub fn line_length(&self) -> Option<usize> {
+        match self.options.line_length {
+            Some(line_length) if line_length > 0 => Some(line_length as usize),
+            _ => None,
+        }
+    }
+
+    pub fn is_verbatim_environment(&self, name: &str) -> bool {
+        VERBATIM_ENVIRONMENTS.contains(&name)
+            || self.verbatim_environments.iter().any(|env| env == name)
+    }
+
+    fn is_protected_environment(&self, environment: &LatexEnvironment) -> bool {
+        let name = environment.left.name().map(LatexToken::text).unwrap_or("");
+        environment.left.is_math()
+            || self.is_verbatim_environment(name)
+            || ALIGNMENT_ENVIRONMENTS.contains(&name)
+    }
+}
+
+impl Default for LatexFormattingParams {
+    fn default() -> Self {
+        Self {
+            tab_size: 4,
+            insert_spaces: true,
+            options: LatexFormattingOptions::default(),
+            verbatim_environments: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LatexBlock {
+    range: Range,
+    indent: bool,
+    protected: bool,
+    verbatim: bool,
+}
+
+impl LatexBlock {
+    fn body(&self, position: Position) -> bool {
+        position >= self.range.start && position < self.range.end
+    }
+}
+
+#[derive(Debug)]
+struct LatexFormatter<'a> {
+    params: &'a LatexFormattingParams,
+    lines: Vec<String>,
+    blocks: Vec<LatexBlock>,
+    inlines: Vec<Range>,
+    indent: String,
+    output: Vec<String>,
+}
+
+impl<'a> LatexFormatter<'a> {
+    fn new(text: &str, tree: &LatexSyntaxTree, params: &'a LatexFormattingParams) -> Self {
+        let indent = if params.insert_spaces {
+            " ".repeat(params.tab_size)
+        } else {
+            "\t".into()
+        };
+
+        let lines = text
+            .split('\n')
+            .map(|line| line.trim_end_matches('\r').to_owned())
+            .collect();
+
+        let mut blocks = Vec::new();
+        for environment in &tree.env.environments {
+            let name = environment.left.name().map(LatexToken::text).unwrap_or("");
+            blocks.push(LatexBlock {
+                range: Range::new(environment.left.end(), environment.right.start()),
+                indent: !environment.is_root(),
+                protected: params.is_protected_environment(environment),
+                verbatim: params.is_verbatim_environment(name),
+            });
+        }
+
+        for equation in &tree.math.equations {
+            blocks.push(LatexBlock {
+                range: Range::new(equation.left.end(), equation.right.start()),
+                indent: false,
+                protected: true,
+                verbatim: false,
+            });
+        }
+
+        let inlines = tree
+            .math
+            .inlines
+            .iter()
+            .map(|inline| inline.range())
+            .collect();
+        Self {
+            params,
+            lines,
+            blocks,
+            inlines,
+            indent,
+            output: Vec::new(),
+        }
+    }
+
+    fn is_protected(&self, position: Position) -> bool {
+        self.blocks
+            .iter()
+            .any(|block| block.protected && block.range.contains_exclusive(position))
+            || self
+                .inlines
+                .iter()
+                .any(|inline| inline.contains_exclusive(position))
+    }
+
+    fn is_protected_line(&self, line: usize) -> bool {
+        self.blocks.iter().any(|block| {
+            block.protected
+                && (block.range.start.line as usize) < line
+                && line < block.range.end.line as usize
+        })
+    }
+
+    /// Returns the column where the body of a verbatim environment starts
+    /// if it continues on the following lines.
+    fn verbatim_start(&self, line: usize) -> Option<u64> {
+        self.blocks
+            .iter()
+            .find(|block| {
+                block.verbatim
+                    && block.range.start.line as usize == line
+                    && block.range.end.line as usize > line
+            })
+            .map(|block| block.range.start.character)
+    }
+
+    /// Returns the column where the body of a verbatim environment ends
+    /// if it started on one of the previous lines.
+    fn verbatim_end(&self, line: usize) -> Option<u64> {
+        self.blocks
+            .iter()
+            .find(|block| {
+                block.verbatim
+                    && (block.range.start.line as usize) < line
+                    && block.range.end.line as usize == line
+            })
+            .map(|block| block.range.end.character)
+    }
+
+    fn split_points(&self, tree: &LatexSyntaxTree) -> Vec<Position> {
+        let mut points = Vec::new();
+        for environment in &tree.env.environments {
+            points.push(environment.left.start());
+            points.push(environment.right.end());
+            if !self.params.is_protected_environment(environment) {
+                points.push(environment.left.end());
+                points.push(environment.right.start());
+            }
+        }
+
+        for item in &tree.structure.items {
+            points.push(item.start());
+        }
+
+        points.retain(|point| !self.is_protected(*point));
+        points.sort();
+        points.dedup();
+        points
+    }
+
+    fn depth(&self, position: Position) -> usize {
+        self.blocks
+            .iter()
+            .filter(|block| block.indent && block.body(position))
+            .count()
+    }
+
+    fn format(&mut self, tree: &LatexSyntaxTree) {
+        let points = self.split_points(tree);
+        for line in 0..self.lines.len() {
+            if self.is_protected_line(line) {
+                let text = self.lines[line].clone();
+                self.output.push(text);
+                continue;
+            }
+
+            // The parts of a verbatim body that share a line with its delimiters are kept as is.
+            let text = self.lines[line].clone();
+            let offset = |character| offset_at(&text, Position::new(0, character));
+            let start = self
+                .verbatim_end(line)
+                .map(offset)
+                .filter(|start| !text[..*start].trim().is_empty())
+                .unwrap_or(0);
+            let end = self
+                .verbatim_start(line)
+                .map(offset)
+                .filter(|end| !text[*end..].trim().is_empty())
+                .unwrap_or_else(|| text.len());
+
+            let mut offsets: Vec<usize> = points
+                .iter()
+                .filter(|point| point.line as usize == line)
+                .map(|point| offset(point.character))
+                .filter(|offset| *offset > start && *offset < end)
+                .collect();
+            offsets.insert(0, start);
+            offsets.push(end);
+
+            let mut pieces = Vec::new();
+            for window in offsets.windows(2) {
+                let piece = &text[window[0]..window[1]];
+                if !piece.trim().is_empty() {
+                    let offset = window[1] - piece.trim_start().len();
+                    let character = text[..offset].encode_utf16().count();
+                    let position = Position::new(line as u64, character as u64);
+                    pieces.push((position, piece.trim().to_owned()));
+                }
+            }
+
+            if pieces.is_empty() {
+                self.output.push(String::new());
+            }
+
+            let last = pieces.len().saturating_sub(1);
+            for (index, (position, piece)) in pieces.into_iter().enumerate() {
+                let indent = if index == 0 && start > 0 {
+                    text[..start].to_owned()
+                } else {
+                    self.indent.repeat(self.depth(position))
+                };
+
+                if index == last && end < text.len() {
+                    self.output
+                        .push(format!("{}{}{}", indent, piece, &text[end..]));
+                } else if index == 0 && start > 0 {
+                    self.output.push(format!("{}{}", indent, piece));
+                } else {
+                    self.push_line(indent, piece);
+                }
+            }
+        }
+    }
+
+    fn push_line(&mut self, indent: String, text: String) {
+        let line_length = match self.params.line_length() {
+            Some(line_length) => line_length,
+            None => {
+                self.output.push(format!("{}{}", indent, text));
+                return;
+            }
+        };
+
+        let length = indent.chars().count() + text.chars().count();
+        if length <= line_length || text.contains('%') || text.contains("\\verb") {
+            self.output.push(format!("{}{}", indent, text));
+            return;
+        }
+
+        let mut current = String::new();
+        for word in split_words(&text) {
+            let length = indent.chars().count() + current.chars().count();
+            if !current.is_empty() && length + 1 + word.chars().count() > line_length {
+                self.output.push(format!("{}{}", indent, current));
+                current.clear();
+            }
+
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        self.output.push(format!("{}{}", indent, current));
+    }
+}
+
+/// Splits the text at whitespace outside of inline math,
+/// so that `$...$` and `\(...\)` are never spread across lines.
+fn split_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut math_end: Option<&str> = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match (chars.next().map(|(_, next)| next), math_end) {
+                (Some('('), None) => math_end = Some("\\)"),
+                (Some(')'), Some("\\)")) => math_end = None,
+                _ => (),
+            },
+            '$' => {
+                let is_double = chars.peek().map_or(false, |(_, next)| *next == '$');
+                if is_double {
+                    chars.next();
+                }
+
+                let delimiter = if is_double { "$$" } else { "$" };
+                match math_end {
+                    None => math_end = Some(delimiter),
+                    Some(end) if end == delimiter => math_end = None,
+                    Some(_) => (),
+                }
+            }
+            c if c.is_whitespace() && math_end.is_none() => {
+                if let Some(start) = start.take() {
+                    words.push(&text[start..i]);
+                }
+                continue;
+            }
+            _ => (),
+        }
+        start.get_or_insert(i);
+    }
+
+    if let Some(start) = start {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+pub fn format_latex(text: &str, tree: &LatexSyntaxTree, params: &LatexFormattingParams) -> String {
+    let mut formatter = LatexFormatter::new(text, tree, params);
+    formatter.format(tree);
+    formatter.output.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SyntaxTreeInput;
+    use indoc::indoc;
+    use texlab_distro::{Language, Resolver};
+    use texlab_protocol::{LatexOptions, Options, Uri};
+
+    fn verify(source: &str, expected: &str, line_length: Option<i32>) {
+        verify_with_verbatim(source, expected, line_length, Vec::new());
+    }
+
+    fn verify_with_verbatim(
+        source: &str,
+        expected: &str,
+        line_length: Option<i32>,
+        verbatim_environments: Vec<String>,
+    ) {
+        let uri = Uri::from_file_path("/foo.tex").unwrap();
+        let options = Options {
+            latex: Some(LatexOptions {
+                verbatim_environments: Some(verbatim_environments.clone()),
+                ..LatexOptions::default()
+            }),
+            ..Options::default()
+        };
+        let tree = LatexSyntaxTree::parse(SyntaxTreeInput {
+            options: &options,
+            resolver: &Resolver::default(),
+            uri: &uri,
+            text: source,
+            language: Language::Latex,
+        });
+        let params = LatexFormattingParams {
+            tab_size: 2,
+            insert_spaces: true,
+            options: LatexFormattingOptions { line_length },
+            verbatim_environments,
+        };
+        assert_eq!(format_latex(source, &tree, &params), expected);
+    }
+
+    #[test]
+    fn environment_indent() {
+        verify(
+            indoc!(
+                r#"
+                    \begin{document}
+                    \begin{center}
+                    foo
+                    \end{center}
+                    \end{document}"#
+            ),
+            indoc!(
+                r#"
+                    \begin{document}
+                    \begin{center}
+                      foo
+                    \end{center}
+                    \end{document}"#
+            ),
+            None,
+        );
+    }
+
+    #[test]
+    fn environment_spacing() {
+        verify(
+            r#"foo   \begin{center}  bar \end{center}   baz"#,
+            indoc!(
+                r#"
+                    foo
+                    \begin{center}
+                      bar
+                    \end{center}
+                    baz"#
+            ),
+            None,
+        );
+    }
+
+    #[test]
+    fn items() {
+        verify(
+            r#"\begin{itemize} \item foo \item bar \end{itemize}"#,
+            indoc!(
+                r#"
+                    \begin{itemize}
+                      \item foo
+                      \item bar
+                    \end{itemize}"#
+            ),
+            None,
+        );
+    }
+
+    #[test]
+    fn verbatim() {
+        let source = indoc!(
+            r#"
+                \begin{itemize}
+                \begin{verbatim}
+                  \item   foo
+                \end{verbatim}
+                \end{itemize}"#
+        );
+        verify(
+            source,
+            indoc!(
+                r#"
+                    \begin{itemize}
+                      \begin{verbatim}
+                      \item   foo
+                      \end{verbatim}
+                    \end{itemize}"#
+            ),
+            None,
+        );
+    }
+
+    #[test]
+    fn items_unicode() {
+        verify(
+            r#"\begin{itemize} \item 😀😀 foo \item bar \end{itemize}"#,
+            indoc!(
+                r#"
+                    \begin{itemize}
+                      \item 😀😀 foo
+                      \item bar
+                    \end{itemize}"#
+            ),
+            None,
+        );
+    }
+
+    #[test]
+    fn verbatim_starred() {
+        let source = indoc!(
+            r#"
+                \begin{itemize}
+                \begin{Verbatim*}
+                \item   foo
+                \end{Verbatim*}
+                \end{itemize}"#
+        );
+        verify(
+            source,
+            indoc!(
+                r#"
+                    \begin{itemize}
+                      \begin{Verbatim*}
+                    \item   foo
+                      \end{Verbatim*}
+                    \end{itemize}"#
+            ),
+            None,
+        );
+    }
+
+    #[test]
+    fn verbatim_custom() {
+        verify_with_verbatim(
+            indoc!(
+                r#"
+                    \begin{code}
+                    \begin{center}   foo
+                    \end{code}"#
+            ),
+            indoc!(
+                r#"
+                    \begin{code}
+                    \begin{center}   foo
+                    \end{code}"#
+            ),
+            None,
+            vec!["code".into()],
+        );
+    }
+
+    #[test]
+    fn verbatim_first_line() {
+        verify(
+            indoc!(
+                r#"
+                    \begin{verbatim}  foo   bar
+                      baz\end{verbatim}"#
+            ),
+            indoc!(
+                r#"
+                    \begin{verbatim}  foo   bar
+                      baz\end{verbatim}"#
+            ),
+            None,
+        );
+    }
+
+    #[test]
+    fn display_math() {
+        verify(
+            indoc!(
+                r#"
+                    \begin{center}
+                    \[
+                      a  &= b
+                    \]
+                    \end{center}"#
+            ),
+            indoc!(
+                r#"
+                    \begin{center}
+                      \[
+                      a  &= b
+                      \]
+                    \end{center}"#
+            ),
+            Some(5),
+        );
+    }
+
+    #[test]
+    fn math_alignment() {
+        verify(
+            indoc!(
+                r#"
+                    \begin{align}
+                    a  &= b \\
+                         &= c
+                    \end{align}"#
+            ),
+            indoc!(
+                r#"
+                    \begin{align}
+                    a  &= b \\
+                         &= c
+                    \end{align}"#
+            ),
+            Some(10),
+        );
+    }
+
+    #[test]
+    fn wrap() {
+        verify(
+            "foo bar baz qux",
+            indoc!(
+                r#"
+                    foo bar
+                    baz qux"#
+            ),
+            Some(8),
+        );
+    }
+
+    #[test]
+    fn wrap_inline_math() {
+        verify(
+            "foo $a + b$ bar \\(c = d\\) baz",
+            indoc!(
+                r#"
+                    foo
+                    $a + b$
+                    bar
+                    \(c = d\)
+                    baz"#
+            ),
+            Some(8),
+        );
+    }
+
+    #[test]
+    fn wrap_comment() {
+        verify("foo bar % baz qux", "foo bar % baz qux", Some(8));
+    }
+}
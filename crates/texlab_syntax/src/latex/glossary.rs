@@ -1,4 +1,5 @@
 use super::ast::*;
+use super::incremental::LatexPatch;
 use crate::language::*;
 use crate::text::SyntaxNode;
 use std::sync::Arc;
@@ -55,4 +56,10 @@ impl LatexGlossaryInfo {
             entries: LatexGlossaryEntry::parse(commands),
         }
     }
+
+    pub(super) fn patch(&self, patch: &LatexPatch, commands: &[Arc<LatexCommand>]) -> Self {
+        Self {
+            entries: patch.apply(&self.entries, LatexGlossaryEntry::parse(commands)),
+        }
+    }
 }
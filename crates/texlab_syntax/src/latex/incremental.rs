@@ -0,0 +1,552 @@
+use super::lexer::{is_word_char, LatexLexer, VERBATIM_COMMANDS, VERBATIM_ENVIRONMENTS};
+use super::*;
+use crate::text::{is_command_char, CharStream, Span, SyntaxNode};
+use crate::SyntaxTreeInput;
+use std::sync::Arc;
+use texlab_protocol::{Position, Range};
+
+/// Describes which items of the previous tree are still valid after an edit.
+/// Items before `start` and items after `reuse_start` are taken over unchanged.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(super) struct LatexPatch {
+    start: Position,
+    reuse_start: Option<Position>,
+}
+
+impl LatexPatch {
+    pub fn apply<T: SyntaxNode + Clone>(&self, old: &[T], new: Vec<T>) -> Vec<T> {
+        self.apply_by(old, new, SyntaxNode::start)
+    }
+
+    fn apply_by<T, F>(&self, old: &[T], new: Vec<T>, start: F) -> Vec<T>
+    where
+        T: Clone,
+        F: Fn(&T) -> Position,
+    {
+        let mut items: Vec<T> = old
+            .iter()
+            .filter(|item| start(item) < self.start)
+            .cloned()
+            .collect();
+        items.extend(new);
+        if let Some(reuse_start) = self.reuse_start {
+            items.extend(
+                old.iter()
+                    .filter(|item| start(item) >= reuse_start)
+                    .cloned(),
+            );
+        }
+        items
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct LatexShift {
+    old_end: Position,
+    new_end: Position,
+}
+
+impl LatexShift {
+    fn new(range: Range, replacement: &str) -> Self {
+        let mut new_end = range.start;
+        for c in replacement.chars() {
+            if c == '\n' {
+                new_end.line += 1;
+                new_end.character = 0;
+            } else {
+                new_end.character += c.len_utf16() as u64;
+            }
+        }
+
+        Self {
+            old_end: range.end,
+            new_end,
+        }
+    }
+
+    /// Content behind the edit keeps its positions only if the edit did not add or remove lines.
+    /// Otherwise, it is shifted by the line delta without running the lexer again.
+    fn is_unchanged(&self, position: Position) -> bool {
+        self.old_end.line == self.new_end.line && position.line > self.old_end.line
+    }
+
+    fn position(&self, position: Position) -> Position {
+        if position.line == self.old_end.line {
+            Position::new(
+                self.new_end.line,
+                position.character - self.old_end.character + self.new_end.character,
+            )
+        } else {
+            Position::new(
+                position.line - self.old_end.line + self.new_end.line,
+                position.character,
+            )
+        }
+    }
+
+    fn token(&self, token: &LatexToken) -> LatexToken {
+        let range = Range::new(self.position(token.start()), self.position(token.end()));
+        LatexToken::new(Span::new(range, token.span.text.clone()), token.kind)
+    }
+
+    fn group(&self, group: &LatexGroup) -> Arc<LatexGroup> {
+        Arc::new(LatexGroup::new(
+            self.token(&group.left),
+            group
+                .children
+                .iter()
+                .map(|child| self.content(child))
+                .collect(),
+            group.right.as_ref().map(|right| self.token(right)),
+            group.kind,
+        ))
+    }
+
+    fn content(&self, content: &LatexContent) -> LatexContent {
+        match content {
+            LatexContent::Group(group) => LatexContent::Group(self.group(group)),
+            LatexContent::Command(command) => {
+                let name = self.token(&command.name);
+                let options = command.options.iter().map(|group| self.group(group));
+                let args = command.args.iter().map(|group| self.group(group));
+                let command = LatexCommand::new(name, options.collect(), args.collect());
+                LatexContent::Command(Arc::new(command))
+            }
+            LatexContent::Text(text) => {
+                let words = text.words.iter().map(|word| self.token(word)).collect();
+                LatexContent::Text(Arc::new(LatexText::new(words)))
+            }
+            LatexContent::Comma(comma) => {
+                LatexContent::Comma(Arc::new(LatexComma::new(self.token(&comma.token))))
+            }
+            LatexContent::Math(math) => {
+                LatexContent::Math(Arc::new(LatexMath::new(self.token(&math.token))))
+            }
+            LatexContent::Verbatim(verbatim) => {
+                LatexContent::Verbatim(Arc::new(LatexVerbatim::new(self.token(&verbatim.token))))
+            }
+        }
+    }
+}
+
+impl LatexSyntaxTree {
+    /// Updates the tree after `range` of the previous text has been replaced with `replacement`.
+    /// Only the top-level content surrounding the edit is parsed again.
+    pub fn reparse(&self, input: SyntaxTreeInput, range: Range, replacement: &str) -> Self {
+        self.reparse_incremental(input, range, replacement)
+            .unwrap_or_else(|| Self::parse(input))
+    }
+
+    fn reparse_incremental(
+        &self,
+        input: SyntaxTreeInput,
+        range: Range,
+        replacement: &str,
+    ) -> Option<Self> {
+        let children = &self.root.children;
+        if children.is_empty() {
+            return None;
+        }
+
+        let verbatim_environments = verbatim_environments(input.options);
+        let changes_state = |content: &LatexContent| {
+            changes_lexer_state(content, &verbatim_environments) || is_verbatim(content)
+        };
+
+        let first = children
+            .iter()
+            .position(|child| child.end() >= range.start)
+            .unwrap_or(children.len() - 1);
+        let last = children
+            .iter()
+            .rposition(|child| child.start() <= range.end)
+            .unwrap_or(0);
+
+        let mut lo = first.min(last).saturating_sub(1);
+        let mut hi = (first.max(last) + 1).min(children.len() - 1);
+        while lo > 0 && changes_state(&children[lo - 1]) {
+            lo -= 1;
+        }
+        while hi + 1 < children.len() && changes_state(&children[hi]) {
+            hi += 1;
+        }
+
+        let shift = LatexShift::new(range, replacement);
+        let at_end = hi + 1 == children.len();
+        let start = if lo == 0 {
+            Position::new(0, 0)
+        } else {
+            children[lo].start()
+        };
+
+        let mut stream = CharStream::new(input.text);
+        stream.seek(start);
+        let start_index = stream.current_index;
+        let end_index = if at_end {
+            input.text.len()
+        } else {
+            stream.seek(shift.position(children[hi].end()));
+            stream.current_index
+        };
+
+        let mut comments = Vec::new();
+        let mut last_token = None;
+        let region = {
+            let lexer = LatexLexer::with_verbatim_environments(
+                &input.text[start_index..end_index],
+                &verbatim_environments,
+            )
+            .starting_at(start);
+            let tokens = lexer.filter(|token| {
+                last_token = Some(token.clone());
+                if token.kind == LatexTokenKind::Comment {
+                    comments.push(LatexComment {
+                        token: token.clone(),
+                    });
+                    false
+                } else {
+                    true
+                }
+            });
+            let mut parser = LatexParser::new(tokens);
+            parser.root().children
+        };
+
+        let previous = if lo == 0 { None } else { children.get(lo - 1) };
+        let next = children.get(hi + 1);
+        if !at_end {
+            let next_char = input.text[end_index..].chars().next();
+            if !last_token.map_or(true, |token| is_complete(&token, next_char))
+                || region.iter().any(is_incomplete)
+                || region.last().map_or(false, changes_state)
+            {
+                return None;
+            }
+        }
+
+        if let (Some(left), Some(right)) = (previous, region.first().or(next)) {
+            if !can_join(left, right) {
+                return None;
+            }
+        }
+
+        if let (Some(left), Some(right)) = (region.last(), next) {
+            if !can_join(left, right) {
+                return None;
+            }
+        }
+
+        let suffix = &children[hi + 1..];
+        let split = suffix
+            .iter()
+            .position(|child| shift.is_unchanged(child.start()))
+            .unwrap_or(suffix.len());
+
+        let mut changed_children = region;
+        changed_children.extend(suffix[..split].iter().map(|child| shift.content(child)));
+        let changed_commands =
+            LatexCommandAnalyzer::parse(Arc::new(LatexRoot::new(changed_children.clone())));
+
+        let patch = LatexPatch {
+            start,
+            reuse_start: suffix.get(split).map(SyntaxNode::start),
+        };
+
+        let mut root_children = children[..lo].to_vec();
+        root_children.extend(changed_children);
+        root_children.extend(suffix[split..].iter().cloned());
+        let root = Arc::new(LatexRoot::new(root_children));
+
+        let mut all_comments: Vec<LatexComment> = self
+            .comments
+            .iter()
+            .filter(|comment| comment.start() < start)
+            .cloned()
+            .collect();
+        all_comments.append(&mut comments);
+        if !at_end {
+            let old_end = children[hi].end();
+            for comment in self.comments.iter().filter(|c| c.start() >= old_end) {
+                if shift.is_unchanged(comment.start()) {
+                    all_comments.push(comment.clone());
+                } else {
+                    all_comments.push(LatexComment {
+                        token: shift.token(&comment.token),
+                    });
+                }
+            }
+        }
+
+        let commands = patch.apply_by(&self.commands, changed_commands.clone(), |command| {
+            command.start()
+        });
+        let includes = patch.apply(
+            &self.includes,
+            LatexInclude::parse(input, &changed_commands),
+        );
+        let components = includes.iter().flat_map(LatexInclude::components).collect();
+        let env = LatexEnvironmentInfo::parse(&commands);
+        let structure = self.structure.patch(&patch, &changed_commands);
+        let citations = patch.apply(&self.citations, LatexCitation::parse(&changed_commands));
+        let math = self
+            .math
+            .patch(&patch, Arc::clone(&root), &commands, &changed_commands);
+        let command_definitions = patch.apply(
+            &self.command_definitions,
+            LatexCommandDefinition::parse(&changed_commands),
+        );
+        let glossary = self.glossary.patch(&patch, &changed_commands);
+        let magic = LatexMagicInfo::parse(input.uri, &root, &all_comments);
+        Some(Self {
+            root,
+            comments: all_comments,
+            commands,
+            includes,
+            components,
+            env,
+            structure,
+            citations,
+            math,
+            command_definitions,
+            glossary,
+            magic,
+        })
+    }
+}
+
+fn changes_lexer_state(content: &LatexContent, verbatim_environments: &[String]) -> bool {
+    match content {
+        LatexContent::Command(command) => {
+            let name = command.name.text();
+            if name == "\\begin" {
+                command.extract_word(0).map_or(false, |word| {
+                    VERBATIM_ENVIRONMENTS.contains(&word.text())
+                        || verbatim_environments
+                            .iter()
+                            .any(|environment| environment == word.text())
+                })
+            } else {
+                VERBATIM_COMMANDS.contains(&name)
+            }
+        }
+        LatexContent::Group(_)
+        | LatexContent::Text(_)
+        | LatexContent::Comma(_)
+        | LatexContent::Math(_)
+        | LatexContent::Verbatim(_) => false,
+    }
+}
+
+fn is_verbatim(content: &LatexContent) -> bool {
+    match content {
+        LatexContent::Verbatim(_) => true,
+        LatexContent::Group(_)
+        | LatexContent::Command(_)
+        | LatexContent::Text(_)
+        | LatexContent::Comma(_)
+        | LatexContent::Math(_) => false,
+    }
+}
+
+/// Checks whether the parser would attach `right` to `left` if they were parsed together.
+fn can_join(left: &LatexContent, right: &LatexContent) -> bool {
+    match (left, right) {
+        (LatexContent::Text(_), LatexContent::Text(_)) => false,
+        (LatexContent::Command(_), LatexContent::Group(_)) => false,
+        (LatexContent::Command(_), LatexContent::Text(text)) => {
+            text.words[0].kind != LatexTokenKind::BeginOptions
+        }
+        (LatexContent::Math(left), LatexContent::Math(right)) => left.end() != right.start(),
+        _ => true,
+    }
+}
+
+/// Checks whether the lexer would continue `token` with the following character.
+fn is_complete(token: &LatexToken, next: Option<char>) -> bool {
+    let next = match next {
+        Some(next) => next,
+        None => return true,
+    };
+
+    match token.kind {
+        LatexTokenKind::Word => !is_word_char(next),
+        LatexTokenKind::Command => {
+            let text = token.text();
+            text != "\\"
+                && !(text.len() > 1 && !text.ends_with('*') && is_command_char(next))
+                && !(next == '*' && !text.ends_with('*'))
+        }
+        LatexTokenKind::Math => token.text() == "$$" || next != '$',
+        LatexTokenKind::Comment => next == '\n' || next == '\r',
+        LatexTokenKind::Verbatim => false,
+        LatexTokenKind::Comma
+        | LatexTokenKind::BeginGroup
+        | LatexTokenKind::EndGroup
+        | LatexTokenKind::BeginOptions
+        | LatexTokenKind::EndOptions => true,
+    }
+}
+
+fn is_incomplete(content: &LatexContent) -> bool {
+    match content {
+        LatexContent::Group(group) => is_incomplete_group(group),
+        LatexContent::Command(command) => command
+            .groups
+            .iter()
+            .any(|group| is_incomplete_group(group)),
+        LatexContent::Text(_)
+        | LatexContent::Comma(_)
+        | LatexContent::Math(_)
+        | LatexContent::Verbatim(_) => false,
+    }
+}
+
+fn is_incomplete_group(group: &LatexGroup) -> bool {
+    group.right.is_none() || group.children.iter().any(is_incomplete)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::edit_text;
+    use indoc::indoc;
+    use texlab_distro::{Language, Resolver};
+    use texlab_protocol::{Options, RangeExt, Uri};
+
+    fn parse(text: &str) -> LatexSyntaxTree {
+        let uri = Uri::from_file_path("/foo.tex").unwrap();
+        LatexSyntaxTree::parse(SyntaxTreeInput {
+            options: &Options::default(),
+            resolver: &Resolver::default(),
+            uri: &uri,
+            text,
+            language: Language::Latex,
+        })
+    }
+
+    fn verify(text: &str, range: Range, replacement: &str) -> LatexSyntaxTree {
+        let old_tree = parse(text);
+        let mut new_text = text.to_owned();
+        edit_text(&mut new_text, range, replacement);
+
+        let uri = Uri::from_file_path("/foo.tex").unwrap();
+        let new_tree = old_tree.reparse(
+            SyntaxTreeInput {
+                options: &Options::default(),
+                resolver: &Resolver::default(),
+                uri: &uri,
+                text: &new_text,
+                language: Language::Latex,
+            },
+            range,
+            replacement,
+        );
+        assert_eq!(new_tree, parse(&new_text));
+        new_tree
+    }
+
+    const TEXT: &str = indoc!(
+        r#"
+            \documentclass{article}
+            \begin{document}
+            \section{Foo}
+            \label{sec:foo} % TODO: bar
+            Some text \cite{foo}.
+            \begin{verbatim}
+            \label{baz} % qux
+            \end{verbatim}
+            \end{document}"#
+    );
+
+    #[test]
+    fn insert_word() {
+        verify(TEXT, Range::new_simple(4, 5, 4, 5), "more ");
+    }
+
+    #[test]
+    fn insert_label() {
+        let tree = verify(TEXT, Range::new_simple(2, 13, 2, 13), "\\label{sec:bar}");
+        assert_eq!(tree.structure.labels.len(), 2);
+    }
+
+    #[test]
+    fn insert_line() {
+        verify(TEXT, Range::new_simple(3, 0, 3, 0), "\\subsection{Bar}\n");
+    }
+
+    #[test]
+    fn insert_line_break() {
+        let old_tree = parse(TEXT);
+        let range = Range::new_simple(2, 13, 2, 13);
+        let mut new_text = TEXT.to_owned();
+        edit_text(&mut new_text, range, "\n");
+
+        let uri = Uri::from_file_path("/foo.tex").unwrap();
+        let input = SyntaxTreeInput {
+            options: &Options::default(),
+            resolver: &Resolver::default(),
+            uri: &uri,
+            text: &new_text,
+            language: Language::Latex,
+        };
+        let new_tree = old_tree.reparse_incremental(input, range, "\n").unwrap();
+        assert_eq!(new_tree, parse(&new_text));
+        assert!(Arc::ptr_eq(&old_tree.commands[0], &new_tree.commands[0]));
+        assert_eq!(new_tree.structure.labels[0].start(), Position::new(4, 0));
+    }
+
+    #[test]
+    fn delete_lines() {
+        verify(TEXT, Range::new_simple(2, 0, 4, 0), "");
+    }
+
+    #[test]
+    fn edit_comment() {
+        verify(TEXT, Range::new_simple(3, 18, 3, 22), "FIXME");
+    }
+
+    #[test]
+    fn edit_verbatim() {
+        verify(TEXT, Range::new_simple(6, 0, 6, 0), "\\end{document} %");
+    }
+
+    #[test]
+    fn unbalanced_group() {
+        verify(TEXT, Range::new_simple(2, 8, 2, 9), "");
+        verify(TEXT, Range::new_simple(4, 0, 4, 0), "{");
+    }
+
+    #[test]
+    fn begin_verbatim() {
+        verify(TEXT, Range::new_simple(4, 0, 4, 0), "\\begin{comment}");
+        verify(TEXT, Range::new_simple(4, 0, 4, 0), "\\verb|");
+    }
+
+    #[test]
+    fn attach_argument() {
+        verify("a \\foo x {bar} b, c", Range::new_simple(0, 7, 0, 8), "");
+        verify("a, \\foo, {bar}, b", Range::new_simple(0, 7, 0, 8), "");
+    }
+
+    #[test]
+    fn split_word() {
+        verify("foo bar baz", Range::new_simple(0, 5, 0, 5), " ");
+        verify("foo b ar baz", Range::new_simple(0, 5, 0, 6), "");
+    }
+
+    #[test]
+    fn start_and_end() {
+        verify(
+            TEXT,
+            Range::new_simple(0, 0, 0, 0),
+            "% !TEX program = xelatex\n",
+        );
+        verify(TEXT, Range::new_simple(8, 14, 8, 14), "\n\\bye");
+    }
+
+    #[test]
+    fn empty() {
+        verify("", Range::new_simple(0, 0, 0, 0), "\\section{Foo}");
+        verify("\\foo", Range::new_simple(0, 0, 0, 4), "");
+    }
+}
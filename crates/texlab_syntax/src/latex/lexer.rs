@@ -1,17 +1,78 @@
 use super::ast::{LatexToken, LatexTokenKind};
 use crate::text::CharStream;
+use texlab_protocol::Position;
+
+pub const VERBATIM_ENVIRONMENTS: &[&str] = &[
+    "verbatim",
+    "verbatim*",
+    "Verbatim",
+    "Verbatim*",
+    "BVerbatim",
+    "BVerbatim*",
+    "LVerbatim",
+    "LVerbatim*",
+    "lstlisting",
+    "minted",
+    "comment",
+];
+
+/// The verbatim environments whose options and arguments precede the verbatim body.
+const VERBATIM_ARGUMENT_ENVIRONMENTS: &[&str] = &[
+    "Verbatim",
+    "Verbatim*",
+    "BVerbatim",
+    "BVerbatim*",
+    "LVerbatim",
+    "LVerbatim*",
+    "lstlisting",
+    "minted",
+];
+
+pub(super) const VERBATIM_COMMANDS: &[&str] = &["\\verb", "\\verb*"];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum LatexLexerState {
+    Normal,
+    Begin,
+    BeginName,
+    BeginEnd(String),
+    Arguments(String, usize),
+    InlineVerbatim,
+}
 
 pub struct LatexLexer<'a> {
     stream: CharStream<'a>,
+    verbatim_environments: Vec<String>,
+    state: LatexLexerState,
 }
 
 impl<'a> LatexLexer<'a> {
     pub fn new(text: &'a str) -> Self {
+        Self::with_verbatim_environments(text, &[])
+    }
+
+    /// Creates a lexer that treats the given environments as verbatim
+    /// in addition to the default ones.
+    pub fn with_verbatim_environments(text: &'a str, environments: &[String]) -> Self {
+        let mut verbatim_environments: Vec<String> = VERBATIM_ENVIRONMENTS
+            .iter()
+            .map(|name| (*name).to_owned())
+            .collect();
+        verbatim_environments.extend(environments.iter().cloned());
         Self {
             stream: CharStream::new(text),
+            verbatim_environments,
+            state: LatexLexerState::Normal,
         }
     }
 
+    /// Continues the positions of the tokens at the given position,
+    /// e.g. when lexing a slice of a larger document.
+    pub fn starting_at(mut self, position: Position) -> Self {
+        self.stream.current_position = position;
+        self
+    }
+
     fn single_char(&mut self, kind: LatexTokenKind) -> LatexToken {
         self.stream.start_span();
         self.stream.next();
@@ -34,6 +95,15 @@ impl<'a> LatexLexer<'a> {
         LatexToken::new(span, LatexTokenKind::Command)
     }
 
+    fn comment(&mut self) -> LatexToken {
+        self.stream.start_span();
+        while self.stream.satifies(|c| *c != '\n' && *c != '\r') {
+            self.stream.next();
+        }
+        let span = self.stream.end_span();
+        LatexToken::new(span, LatexTokenKind::Comment)
+    }
+
     fn word(&mut self) -> LatexToken {
         self.stream.start_span();
         self.stream.next();
@@ -44,16 +114,12 @@ impl<'a> LatexLexer<'a> {
         let span = self.stream.end_span();
         LatexToken::new(span, LatexTokenKind::Word)
     }
-}
-
-impl<'a> Iterator for LatexLexer<'a> {
-    type Item = LatexToken;
 
-    fn next(&mut self) -> Option<LatexToken> {
+    fn token(&mut self) -> Option<LatexToken> {
         loop {
             match self.stream.peek() {
                 Some('%') => {
-                    self.stream.skip_rest_of_line();
+                    return Some(self.comment());
                 }
                 Some('{') => {
                     return Some(self.single_char(LatexTokenKind::BeginGroup));
@@ -89,9 +155,122 @@ impl<'a> Iterator for LatexLexer<'a> {
             }
         }
     }
+
+    fn update_state(&mut self, token: &LatexToken) {
+        let state = std::mem::replace(&mut self.state, LatexLexerState::Normal);
+        self.state = match (state, token.kind) {
+            (_, LatexTokenKind::Command) if token.text() == "\\begin" => LatexLexerState::Begin,
+            (_, LatexTokenKind::Command) if VERBATIM_COMMANDS.contains(&token.text()) => {
+                LatexLexerState::InlineVerbatim
+            }
+            (LatexLexerState::Begin, LatexTokenKind::BeginGroup) => LatexLexerState::BeginName,
+            (LatexLexerState::BeginName, LatexTokenKind::Word)
+                if self
+                    .verbatim_environments
+                    .iter()
+                    .any(|name| name == token.text()) =>
+            {
+                LatexLexerState::BeginEnd(token.text().to_owned())
+            }
+            (LatexLexerState::BeginEnd(name), LatexTokenKind::EndGroup) => {
+                LatexLexerState::Arguments(name, 0)
+            }
+            (LatexLexerState::Arguments(name, depth), LatexTokenKind::BeginGroup)
+            | (LatexLexerState::Arguments(name, depth), LatexTokenKind::BeginOptions) => {
+                LatexLexerState::Arguments(name, depth + 1)
+            }
+            (LatexLexerState::Arguments(name, depth), LatexTokenKind::EndGroup)
+            | (LatexLexerState::Arguments(name, depth), LatexTokenKind::EndOptions)
+                if depth > 0 =>
+            {
+                LatexLexerState::Arguments(name, depth - 1)
+            }
+            (LatexLexerState::Arguments(name, depth), _) if depth > 0 => {
+                LatexLexerState::Arguments(name, depth)
+            }
+            _ => LatexLexerState::Normal,
+        };
+    }
+
+    fn verbatim(&mut self) -> Option<LatexToken> {
+        match self.state.clone() {
+            LatexLexerState::Arguments(name, 0) => {
+                if VERBATIM_ARGUMENT_ENVIRONMENTS.contains(&name.as_str()) {
+                    while self.stream.satifies(|c| *c == ' ' || *c == '\t') {
+                        self.stream.next();
+                    }
+
+                    if self.stream.satifies(|c| *c == '{' || *c == '[') {
+                        return None;
+                    }
+                }
+
+                self.state = LatexLexerState::Normal;
+                self.verbatim_environment(&name)
+            }
+            LatexLexerState::InlineVerbatim => {
+                self.state = LatexLexerState::Normal;
+                self.verbatim_inline()
+            }
+            _ => None,
+        }
+    }
+
+    fn verbatim_environment(&mut self, name: &str) -> Option<LatexToken> {
+        let end = format!("\\end{{{}}}", name);
+        let rest = self.stream.rest();
+        let length = rest.find(&end).unwrap_or(rest.len());
+        let count = rest[..length].chars().count();
+        if count == 0 {
+            return None;
+        }
+
+        self.stream.start_span();
+        for _ in 0..count {
+            self.stream.next();
+        }
+        let span = self.stream.end_span();
+        Some(LatexToken::new(span, LatexTokenKind::Verbatim))
+    }
+
+    fn verbatim_inline(&mut self) -> Option<LatexToken> {
+        let delimiter = self
+            .stream
+            .peek()
+            .filter(|c| !c.is_whitespace() && !c.is_alphabetic())?;
+
+        self.stream.start_span();
+        self.stream.next();
+        while self
+            .stream
+            .satifies(|c| *c != delimiter && *c != '\n' && *c != '\r')
+        {
+            self.stream.next();
+        }
+
+        if self.stream.satifies(|c| *c == delimiter) {
+            self.stream.next();
+        }
+        let span = self.stream.end_span();
+        Some(LatexToken::new(span, LatexTokenKind::Verbatim))
+    }
+}
+
+impl<'a> Iterator for LatexLexer<'a> {
+    type Item = LatexToken;
+
+    fn next(&mut self) -> Option<LatexToken> {
+        if let Some(token) = self.verbatim() {
+            return Some(token);
+        }
+
+        let token = self.token()?;
+        self.update_state(&token);
+        Some(token)
+    }
 }
 
-fn is_word_char(c: char) -> bool {
+pub(super) fn is_word_char(c: char) -> bool {
     !c.is_whitespace()
         && c != '%'
         && c != '{'
@@ -117,7 +296,7 @@ mod tests {
         kind: LatexTokenKind,
     ) {
         let start = Position::new(line, character);
-        let end = Position::new(line, character + text.chars().count() as u64);
+        let end = Position::new(line, character + text.encode_utf16().count() as u64);
         let range = Range::new(start, end);
         let span = Span::new(range, text.to_owned());
         let token = LatexToken::new(span, kind);
@@ -173,7 +352,102 @@ mod tests {
     #[test]
     fn line_comment() {
         let mut lexer = LatexLexer::new(" %foo \nfoo");
+        verify(&mut lexer, 0, 1, "%foo ", LatexTokenKind::Comment);
         verify(&mut lexer, 1, 0, "foo", LatexTokenKind::Word);
         assert_eq!(None, lexer.next());
     }
+
+    #[test]
+    fn line_comment_end_of_file() {
+        let mut lexer = LatexLexer::new("\\foo%bar");
+        verify(&mut lexer, 0, 0, "\\foo", LatexTokenKind::Command);
+        verify(&mut lexer, 0, 4, "%bar", LatexTokenKind::Comment);
+        assert_eq!(None, lexer.next());
+    }
+
+    #[test]
+    fn verbatim_environment() {
+        let mut lexer = LatexLexer::new("\\begin{verbatim}%foo\\label{bar}\\end{verbatim}");
+        verify(&mut lexer, 0, 0, "\\begin", LatexTokenKind::Command);
+        verify(&mut lexer, 0, 6, "{", LatexTokenKind::BeginGroup);
+        verify(&mut lexer, 0, 7, "verbatim", LatexTokenKind::Word);
+        verify(&mut lexer, 0, 15, "}", LatexTokenKind::EndGroup);
+        verify(
+            &mut lexer,
+            0,
+            16,
+            "%foo\\label{bar}",
+            LatexTokenKind::Verbatim,
+        );
+        verify(&mut lexer, 0, 31, "\\end", LatexTokenKind::Command);
+        verify(&mut lexer, 0, 35, "{", LatexTokenKind::BeginGroup);
+        verify(&mut lexer, 0, 36, "verbatim", LatexTokenKind::Word);
+        verify(&mut lexer, 0, 44, "}", LatexTokenKind::EndGroup);
+        assert_eq!(None, lexer.next());
+    }
+
+    #[test]
+    fn verbatim_environment_brackets() {
+        let mut lexer = LatexLexer::new("\\begin{verbatim}[a]\\end{verbatim}");
+        for _ in 0..4 {
+            lexer.next();
+        }
+        verify(&mut lexer, 0, 16, "[a]", LatexTokenKind::Verbatim);
+        verify(&mut lexer, 0, 19, "\\end", LatexTokenKind::Command);
+    }
+
+    #[test]
+    fn verbatim_environment_arguments() {
+        let mut lexer = LatexLexer::new("\\begin{minted}[a]{c}%{\\end{minted}");
+        verify(&mut lexer, 0, 0, "\\begin", LatexTokenKind::Command);
+        verify(&mut lexer, 0, 6, "{", LatexTokenKind::BeginGroup);
+        verify(&mut lexer, 0, 7, "minted", LatexTokenKind::Word);
+        verify(&mut lexer, 0, 13, "}", LatexTokenKind::EndGroup);
+        verify(&mut lexer, 0, 14, "[", LatexTokenKind::BeginOptions);
+        verify(&mut lexer, 0, 15, "a", LatexTokenKind::Word);
+        verify(&mut lexer, 0, 16, "]", LatexTokenKind::EndOptions);
+        verify(&mut lexer, 0, 17, "{", LatexTokenKind::BeginGroup);
+        verify(&mut lexer, 0, 18, "c", LatexTokenKind::Word);
+        verify(&mut lexer, 0, 19, "}", LatexTokenKind::EndGroup);
+        verify(&mut lexer, 0, 20, "%{", LatexTokenKind::Verbatim);
+        verify(&mut lexer, 0, 22, "\\end", LatexTokenKind::Command);
+    }
+
+    #[test]
+    fn verbatim_environment_multiline() {
+        let mut lexer = LatexLexer::new("\\begin{lstlisting}[x]\n\\begin{foo}\n\\end{lstlisting}");
+        for _ in 0..7 {
+            lexer.next();
+        }
+        let token = lexer.next().unwrap();
+        assert_eq!(token.kind, LatexTokenKind::Verbatim);
+        assert_eq!(token.text(), "\n\\begin{foo}\n");
+        assert_eq!(
+            token.span.range,
+            Range::new(Position::new(0, 21), Position::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn verbatim_environment_custom() {
+        let environments = vec!["code".to_owned()];
+        let mut lexer =
+            LatexLexer::with_verbatim_environments("\\begin{code}%\\end{code}", &environments);
+        for _ in 0..4 {
+            lexer.next();
+        }
+        verify(&mut lexer, 0, 12, "%", LatexTokenKind::Verbatim);
+        verify(&mut lexer, 0, 13, "\\end", LatexTokenKind::Command);
+    }
+
+    #[test]
+    fn verbatim_command() {
+        let mut lexer = LatexLexer::new("\\verb|%{|\\verb*+}+ foo");
+        verify(&mut lexer, 0, 0, "\\verb", LatexTokenKind::Command);
+        verify(&mut lexer, 0, 5, "|%{|", LatexTokenKind::Verbatim);
+        verify(&mut lexer, 0, 9, "\\verb*", LatexTokenKind::Command);
+        verify(&mut lexer, 0, 15, "+}+", LatexTokenKind::Verbatim);
+        verify(&mut lexer, 0, 19, "foo", LatexTokenKind::Word);
+        assert_eq!(None, lexer.next());
+    }
 }
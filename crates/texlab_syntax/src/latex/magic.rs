@@ -0,0 +1,180 @@
+use super::ast::LatexRoot;
+use super::LatexComment;
+use crate::text::SyntaxNode;
+use path_clean::PathClean;
+use std::path::PathBuf;
+use texlab_protocol::Uri;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum LatexMagicCommentKind {
+    Tex,
+    Bib,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct LatexMagicInfo {
+    pub root: Option<Uri>,
+    pub program: Option<String>,
+    pub bib_program: Option<String>,
+}
+
+impl LatexMagicInfo {
+    /// Reads the magic comments of the leading comment block,
+    /// i.e. the comments in front of the first content of the document.
+    pub fn parse(uri: &Uri, root: &LatexRoot, comments: &[LatexComment]) -> Self {
+        let content_start = root.children.first().map(SyntaxNode::start);
+        let leading_comments = comments
+            .iter()
+            .take_while(|comment| content_start.map_or(true, |start| comment.start() < start));
+
+        let mut info = Self::default();
+        for comment in leading_comments {
+            let (kind, key, value) = match parse_magic_comment(comment.token.text()) {
+                Some(comment) => comment,
+                None => continue,
+            };
+
+            let is_program =
+                key.eq_ignore_ascii_case("program") || key.eq_ignore_ascii_case("ts-program");
+            match kind {
+                LatexMagicCommentKind::Tex if key.eq_ignore_ascii_case("root") => {
+                    if info.root.is_none() {
+                        info.root = resolve_root(uri, value);
+                    }
+                }
+                LatexMagicCommentKind::Tex if is_program => {
+                    info.program.get_or_insert_with(|| value.to_owned());
+                }
+                LatexMagicCommentKind::Bib if is_program => {
+                    info.bib_program.get_or_insert_with(|| value.to_owned());
+                }
+                LatexMagicCommentKind::Tex | LatexMagicCommentKind::Bib => (),
+            }
+        }
+        info
+    }
+}
+
+fn parse_magic_comment(comment: &str) -> Option<(LatexMagicCommentKind, &str, &str)> {
+    if !comment.starts_with('%') {
+        return None;
+    }
+
+    let line = comment[1..].trim_start();
+    if !line.starts_with('!') {
+        return None;
+    }
+
+    let line = &line[1..];
+    let kind = match line.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("tex") => LatexMagicCommentKind::Tex,
+        Some(prefix) if prefix.eq_ignore_ascii_case("bib") => LatexMagicCommentKind::Bib,
+        _ => return None,
+    };
+
+    let line = &line[3..];
+    let index = line.find('=')?;
+    let key = line[..index].trim();
+    let value = line[index + 1..].trim();
+    if key.is_empty() || value.is_empty() {
+        None
+    } else {
+        Some((kind, key, value))
+    }
+}
+
+fn resolve_root(uri: &Uri, value: &str) -> Option<Uri> {
+    let mut path = uri.to_file_path().ok()?;
+    path.pop();
+    path.push(value);
+    let mut path = PathBuf::from(path.to_string_lossy().into_owned().replace('\\', "/")).clean();
+    if path.extension().is_none() {
+        path.set_extension("tex");
+    }
+    Uri::from_file_path(path).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::latex::LatexSyntaxTree;
+    use crate::SyntaxTreeInput;
+    use indoc::indoc;
+    use texlab_distro::{Language, Resolver};
+    use texlab_protocol::Options;
+
+    fn parse(text: &str) -> LatexMagicInfo {
+        let uri = Uri::from_file_path("/foo/chapters/bar.tex").unwrap();
+        let tree = LatexSyntaxTree::parse(SyntaxTreeInput {
+            options: &Options::default(),
+            resolver: &Resolver::default(),
+            uri: &uri,
+            text,
+            language: Language::Latex,
+        });
+        tree.magic
+    }
+
+    #[test]
+    fn root() {
+        let info = parse("% !TEX root = ../main.tex");
+        let expected = Uri::from_file_path("/foo/main.tex").unwrap();
+        assert_eq!(info.root, Some(expected));
+    }
+
+    #[test]
+    fn root_without_extension() {
+        let info = parse("%!TEX root=../main");
+        let expected = Uri::from_file_path("/foo/main.tex").unwrap();
+        assert_eq!(info.root, Some(expected));
+    }
+
+    #[test]
+    fn program() {
+        let info = parse(indoc!(
+            r#"
+                % !TEX TS-program = xelatex
+                % !BIB program = biber
+                \documentclass{article}"#
+        ));
+        assert_eq!(info.program.as_deref(), Some("xelatex"));
+        assert_eq!(info.bib_program.as_deref(), Some("biber"));
+    }
+
+    #[test]
+    fn first_occurrence() {
+        let info = parse(indoc!(
+            r#"
+                % !TEX program = lualatex
+                % !TEX program = xelatex"#
+        ));
+        assert_eq!(info.program.as_deref(), Some("lualatex"));
+    }
+
+    #[test]
+    fn verbatim() {
+        let info = parse(indoc!(
+            r#"
+                \begin{verbatim}
+                % !TEX program = xelatex
+                \end{verbatim}"#
+        ));
+        assert_eq!(info, LatexMagicInfo::default());
+    }
+
+    #[test]
+    fn after_content() {
+        let info = parse(indoc!(
+            r#"
+                \documentclass{article}
+                % !TEX program = xelatex"#
+        ));
+        assert_eq!(info, LatexMagicInfo::default());
+    }
+
+    #[test]
+    fn ordinary_comment() {
+        let info = parse("% TEX root = ../main.tex");
+        assert_eq!(info, LatexMagicInfo::default());
+    }
+}
@@ -1,4 +1,5 @@
 use super::ast::*;
+use super::incremental::LatexPatch;
 use crate::language::*;
 use crate::text::SyntaxNode;
 use std::sync::Arc;
@@ -98,6 +99,10 @@ impl LatexVisitor for LatexInlineAnalyzer {
         }
         LatexWalker::walk_math(self, math);
     }
+
+    fn visit_verbatim(&mut self, verbatim: Arc<LatexVerbatim>) {
+        LatexWalker::walk_verbatim(self, verbatim);
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -196,4 +201,24 @@ impl LatexMathInfo {
             theorem_definitions: LatexTheoremDefinition::parse(commands),
         }
     }
+
+    /// Equations and inlines are matched across the whole document, so only
+    /// the operators and theorem definitions can be patched.
+    pub(super) fn patch(
+        &self,
+        patch: &LatexPatch,
+        root: Arc<LatexRoot>,
+        commands: &[Arc<LatexCommand>],
+        changed_commands: &[Arc<LatexCommand>],
+    ) -> Self {
+        Self {
+            equations: LatexEquation::parse(commands),
+            inlines: LatexInline::parse(root),
+            operators: patch.apply(&self.operators, LatexMathOperator::parse(changed_commands)),
+            theorem_definitions: patch.apply(
+                &self.theorem_definitions,
+                LatexTheoremDefinition::parse(changed_commands),
+            ),
+        }
+    }
 }
@@ -1,8 +1,11 @@
 mod ast;
 mod env;
 mod finder;
+mod formatting;
 mod glossary;
+mod incremental;
 mod lexer;
+mod magic;
 mod math;
 mod parser;
 mod printer;
@@ -11,7 +14,9 @@ mod structure;
 pub use self::ast::*;
 pub use self::env::*;
 pub use self::finder::LatexNode;
+pub use self::formatting::*;
 pub use self::glossary::*;
+pub use self::magic::LatexMagicInfo;
 pub use self::math::*;
 pub use self::printer::LatexPrinter;
 pub use self::structure::*;
@@ -23,10 +28,11 @@ use super::language::*;
 use super::text::SyntaxNode;
 use super::SyntaxTreeInput;
 use path_clean::PathClean;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
 use texlab_distro::Resolver;
-use texlab_protocol::{Position, Range, RangeExt, Uri};
+use texlab_protocol::{Options, Position, Range, RangeExt, Uri};
 
 #[derive(Debug, Default)]
 struct LatexCommandAnalyzer {
@@ -66,6 +72,27 @@ impl LatexVisitor for LatexCommandAnalyzer {
     fn visit_math(&mut self, math: Arc<LatexMath>) {
         LatexWalker::walk_math(self, math);
     }
+
+    fn visit_verbatim(&mut self, verbatim: Arc<LatexVerbatim>) {
+        LatexWalker::walk_verbatim(self, verbatim);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct LatexComment {
+    pub token: LatexToken,
+}
+
+impl LatexComment {
+    pub fn text(&self) -> &str {
+        &self.token.text()[1..]
+    }
+}
+
+impl SyntaxNode for LatexComment {
+    fn range(&self) -> Range {
+        self.token.range()
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -256,6 +283,25 @@ impl LatexCommandDefinition {
         }
         definitions
     }
+
+    pub fn argument_count(&self) -> usize {
+        self.command
+            .options
+            .get(self.argument_count_index)
+            .and_then(|options| options.children.get(0))
+            .and_then(|child| match child {
+                LatexContent::Text(text) => text.words.get(0),
+                _ => None,
+            })
+            .and_then(|word| word.text().parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Checks whether the first argument is optional, e.g. `\newcommand{\foo}[2][bar]{...}`.
+    /// The optional argument is included in the argument count.
+    pub fn has_optional_argument(&self) -> bool {
+        self.command.options.len() > self.argument_count_index + 1
+    }
 }
 
 impl SyntaxNode for LatexCommandDefinition {
@@ -264,9 +310,18 @@ impl SyntaxNode for LatexCommandDefinition {
     }
 }
 
+pub(crate) fn verbatim_environments(options: &Options) -> Vec<String> {
+    options
+        .latex
+        .as_ref()
+        .and_then(|latex| latex.verbatim_environments.clone())
+        .unwrap_or_default()
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct LatexSyntaxTree {
     pub root: Arc<LatexRoot>,
+    pub comments: Vec<LatexComment>,
     pub commands: Vec<Arc<LatexCommand>>,
     pub includes: Vec<LatexInclude>,
     pub components: Vec<String>,
@@ -276,13 +331,37 @@ pub struct LatexSyntaxTree {
     pub math: LatexMathInfo,
     pub command_definitions: Vec<LatexCommandDefinition>,
     pub glossary: LatexGlossaryInfo,
+    pub magic: LatexMagicInfo,
 }
 
 impl LatexSyntaxTree {
     pub fn parse(input: SyntaxTreeInput) -> Self {
-        let lexer = LatexLexer::new(input.text);
-        let mut parser = LatexParser::new(lexer);
-        let root = Arc::new(parser.root());
+        let mut comments = Vec::new();
+        let root = {
+            let verbatim_environments = verbatim_environments(input.options);
+            let lexer = LatexLexer::with_verbatim_environments(input.text, &verbatim_environments);
+            let tokens = lexer.filter(|token| {
+                if token.kind == LatexTokenKind::Comment {
+                    comments.push(LatexComment {
+                        token: token.clone(),
+                    });
+                    false
+                } else {
+                    true
+                }
+            });
+            let mut parser = LatexParser::new(tokens);
+            Arc::new(parser.root())
+        };
+        Self::analyze(input, root, comments)
+    }
+
+    /// Builds the tree from an already parsed root, e.g. one restored from the document cache.
+    pub fn analyze(
+        input: SyntaxTreeInput,
+        root: Arc<LatexRoot>,
+        comments: Vec<LatexComment>,
+    ) -> Self {
         let commands = LatexCommandAnalyzer::parse(Arc::clone(&root));
         let includes = LatexInclude::parse(input, &commands);
         let components = includes.iter().flat_map(LatexInclude::components).collect();
@@ -292,8 +371,10 @@ impl LatexSyntaxTree {
         let math = LatexMathInfo::parse(Arc::clone(&root), &commands);
         let command_definitions = LatexCommandDefinition::parse(&commands);
         let glossary = LatexGlossaryInfo::parse(&commands);
+        let magic = LatexMagicInfo::parse(input.uri, &root, &comments);
         Self {
             root,
+            comments,
             commands,
             includes,
             components,
@@ -303,6 +384,7 @@ impl LatexSyntaxTree {
             math,
             command_definitions,
             glossary,
+            magic,
         }
     }
 
@@ -32,6 +32,10 @@ impl<I: Iterator<Item = LatexToken>> LatexParser<I> {
                 LatexTokenKind::Word | LatexTokenKind::BeginOptions => {
                     children.push(LatexContent::Text(self.text(scope)));
                 }
+                LatexTokenKind::Verbatim => {
+                    let node = LatexVerbatim::new(self.tokens.next().unwrap());
+                    children.push(LatexContent::Verbatim(Arc::new(node)));
+                }
                 LatexTokenKind::Command => {
                     children.push(LatexContent::Command(self.command()));
                 }
@@ -52,6 +56,9 @@ impl<I: Iterator<Item = LatexToken>> LatexParser<I> {
                         return children;
                     }
                 }
+                LatexTokenKind::Comment => {
+                    self.tokens.next();
+                }
                 LatexTokenKind::EndOptions => {
                     if scope == LatexScope::Options {
                         return children;
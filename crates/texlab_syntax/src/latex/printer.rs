@@ -74,4 +74,8 @@ impl LatexVisitor for LatexPrinter {
     fn visit_math(&mut self, math: Arc<LatexMath>) {
         self.print_token(&math.token)
     }
+
+    fn visit_verbatim(&mut self, verbatim: Arc<LatexVerbatim>) {
+        self.print_token(&verbatim.token)
+    }
 }
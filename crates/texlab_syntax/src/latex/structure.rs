@@ -1,4 +1,5 @@
 use super::ast::*;
+use super::incremental::LatexPatch;
 use crate::language::*;
 use crate::text::{CharStream, SyntaxNode};
 use itertools::Itertools;
@@ -142,6 +143,10 @@ impl LatexLabelNumbering {
             fn visit_math(&mut self, math: Arc<LatexMath>) {
                 LatexWalker::walk_math(self, math);
             }
+
+            fn visit_verbatim(&mut self, verbatim: Arc<LatexVerbatim>) {
+                LatexWalker::walk_verbatim(self, verbatim);
+            }
         }
 
         if command.name.text() != "\\newlabel" || !command.has_word(0) {
@@ -246,4 +251,15 @@ impl LatexStructureInfo {
             items: LatexItem::parse(commands),
         }
     }
+
+    pub(super) fn patch(&self, patch: &LatexPatch, commands: &[Arc<LatexCommand>]) -> Self {
+        Self {
+            sections: patch.apply(&self.sections, LatexSection::parse(commands)),
+            labels: patch.apply(&self.labels, LatexLabel::parse(commands)),
+            label_numberings: patch
+                .apply(&self.label_numberings, LatexLabelNumbering::parse(commands)),
+            captions: patch.apply(&self.captions, LatexCaption::parse(commands)),
+            items: patch.apply(&self.items, LatexItem::parse(commands)),
+        }
+    }
 }
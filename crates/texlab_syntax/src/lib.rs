@@ -10,9 +10,11 @@ pub use self::latex::*;
 pub use self::lsp_kind::*;
 pub use self::text::*;
 
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::Arc;
 use texlab_distro::{Language, Resolver};
-use texlab_protocol::{Options, Uri};
+use texlab_protocol::{Options, Range, Uri};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct SyntaxTreeInput<'a> {
@@ -52,4 +54,75 @@ impl SyntaxTree {
             Language::Bibtex => SyntaxTree::Bibtex(Box::new(input.text.into())),
         }
     }
+
+    pub fn reparse(&self, input: SyntaxTreeInput, range: Range, replacement: &str) -> Self {
+        match (self, input.language) {
+            (SyntaxTree::Latex(tree), Language::Latex) => {
+                SyntaxTree::Latex(Box::new(tree.reparse(input, range, replacement)))
+            }
+            _ => Self::parse(input),
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        match self {
+            SyntaxTree::Latex(_) => Language::Latex,
+            SyntaxTree::Bibtex(_) => Language::Bibtex,
+        }
+    }
+
+    pub fn snapshot(&self, options: &Options) -> SyntaxTreeSnapshot {
+        match self {
+            SyntaxTree::Latex(tree) => SyntaxTreeSnapshot::Latex {
+                root: Arc::clone(&tree.root),
+                comments: tree.comments.clone(),
+                verbatim_environments: latex::verbatim_environments(options),
+            },
+            SyntaxTree::Bibtex(tree) => SyntaxTreeSnapshot::Bibtex(tree.root.clone()),
+        }
+    }
+
+    /// Rebuilds the tree from a snapshot without lexing the text again.
+    /// Falls back to a full parse if the snapshot does not match the input.
+    pub fn restore(input: SyntaxTreeInput, snapshot: SyntaxTreeSnapshot) -> Self {
+        match (snapshot, input.language) {
+            (
+                SyntaxTreeSnapshot::Latex {
+                    root,
+                    comments,
+                    verbatim_environments,
+                },
+                Language::Latex,
+            ) if verbatim_environments == latex::verbatim_environments(input.options) => {
+                SyntaxTree::Latex(Box::new(LatexSyntaxTree::analyze(input, root, comments)))
+            }
+            (SyntaxTreeSnapshot::Bibtex(root), Language::Bibtex) => {
+                SyntaxTree::Bibtex(Box::new(root.into()))
+            }
+            _ => Self::parse(input),
+        }
+    }
+}
+
+/// The parsed form of a document that can be persisted across sessions.
+/// Derived information is recomputed when the snapshot is restored.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyntaxTreeSnapshot {
+    #[serde(rename_all = "camelCase")]
+    Latex {
+        root: Arc<LatexRoot>,
+        comments: Vec<LatexComment>,
+        verbatim_environments: Vec<String>,
+    },
+    Bibtex(BibtexRoot),
+}
+
+impl SyntaxTreeSnapshot {
+    pub fn language(&self) -> Language {
+        match self {
+            SyntaxTreeSnapshot::Latex { .. } => Language::Latex,
+            SyntaxTreeSnapshot::Bibtex(_) => Language::Bibtex,
+        }
+    }
 }
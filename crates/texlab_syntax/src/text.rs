@@ -59,6 +59,10 @@ impl<'a> CharStream<'a> {
         self.chars.peek().map(|(_, c)| *c)
     }
 
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.current_index..]
+    }
+
     pub fn satifies<P: FnOnce(&char) -> bool>(&mut self, predicate: P) -> bool {
         self.peek().filter(predicate).is_some()
     }
@@ -120,7 +124,7 @@ impl<'a> CharStream<'a> {
             self.current_position.line += 1;
             self.current_position.character = 0;
         } else {
-            self.current_position.character += 1;
+            self.current_position.character += c.len_utf16() as u64;
         }
     }
 
@@ -147,10 +151,37 @@ impl<'a> Iterator for CharStream<'a> {
     }
 }
 
-fn is_command_char(c: char) -> bool {
+pub(crate) fn is_command_char(c: char) -> bool {
     c >= 'a' && c <= 'z' || c >= 'A' && c <= 'Z' || c == '@'
 }
 
+pub fn offset_at(text: &str, position: Position) -> usize {
+    let mut line = 0;
+    let mut character = 0;
+    for (index, c) in text.char_indices() {
+        if line == position.line && character >= position.character {
+            return index;
+        }
+
+        if c == '\n' {
+            if line == position.line {
+                return index;
+            }
+            line += 1;
+            character = 0;
+        } else {
+            character += c.len_utf16() as u64;
+        }
+    }
+    text.len()
+}
+
+pub fn edit_text(text: &mut String, range: Range, replacement: &str) {
+    let start = offset_at(text, range.start);
+    let end = offset_at(text, range.end).max(start);
+    text.replace_range(start..end, replacement);
+}
+
 pub fn titlelize(string: &str) -> String {
     let mut chars = string.chars();
     match chars.next() {
@@ -204,7 +235,7 @@ mod tests {
         stream.next();
         let span = stream.end_span();
         assert_eq!(
-            Span::new(Range::new_simple(0, 1, 0, 3), "😃😄".to_owned()),
+            Span::new(Range::new_simple(0, 2, 0, 6), "😃😄".to_owned()),
             span
         );
     }
@@ -265,4 +296,42 @@ mod tests {
             span
         );
     }
+
+    #[test]
+    fn offset_at_basic() {
+        let text = "abc\ndef";
+        assert_eq!(0, offset_at(text, Position::new(0, 0)));
+        assert_eq!(2, offset_at(text, Position::new(0, 2)));
+        assert_eq!(5, offset_at(text, Position::new(1, 1)));
+        assert_eq!(7, offset_at(text, Position::new(1, 3)));
+    }
+
+    #[test]
+    fn offset_at_end_of_line() {
+        let text = "abc\ndef";
+        assert_eq!(3, offset_at(text, Position::new(0, 10)));
+        assert_eq!(7, offset_at(text, Position::new(5, 0)));
+    }
+
+    #[test]
+    fn offset_at_utf16() {
+        let text = "😀ä😃b";
+        assert_eq!(4, offset_at(text, Position::new(0, 2)));
+        assert_eq!(6, offset_at(text, Position::new(0, 3)));
+        assert_eq!(10, offset_at(text, Position::new(0, 5)));
+    }
+
+    #[test]
+    fn edit_text_insert() {
+        let mut text = String::from("foo\nbaz");
+        edit_text(&mut text, Range::new_simple(1, 0, 1, 0), "bar\n");
+        assert_eq!("foo\nbar\nbaz", text);
+    }
+
+    #[test]
+    fn edit_text_replace_multiline() {
+        let mut text = String::from("foo\nbar\nbaz");
+        edit_text(&mut text, Range::new_simple(0, 1, 2, 2), "ua");
+        assert_eq!("fuaz", text);
+    }
 }
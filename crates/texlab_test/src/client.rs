@@ -9,6 +9,7 @@ use texlab_protocol::*;
 pub struct MockLspClient {
     pub messages: Mutex<Vec<ShowMessageParams>>,
     pub options: Mutex<Options>,
+    pub scoped_options: Mutex<HashMap<Uri, Options>>,
     pub diagnostics_by_uri: Mutex<HashMap<Uri, Vec<Diagnostic>>>,
     pub log_messages: Mutex<Vec<LogMessageParams>>,
 }
@@ -43,7 +44,13 @@ impl LspClient for MockLspClient {
                 .ok_or_else(|| jsonrpc::Error::internal_error("Internal error".to_owned()))
         }
 
-        let options = self.options.lock().await;
+        let scope: Option<Uri> = params.items[0].scope_uri.clone().map(Into::into);
+        let scoped_options = self.scoped_options.lock().await;
+        let options = match scope.and_then(|scope| scoped_options.get(&scope)) {
+            Some(options) => options.clone(),
+            None => self.options.lock().await.clone(),
+        };
+
         match params.items[0].section.as_ref().unwrap().as_ref() {
             "latex" => serialize(&options.latex),
             "bibtex" => serialize(&options.bibtex),
@@ -7,25 +7,55 @@ pub async fn run_bibtex(
     file: &'static str,
     options: Option<BibtexFormattingOptions>,
 ) -> (Scenario, Vec<TextEdit>) {
-    let scenario = Scenario::new("formatting/bibtex", false).await;
-    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
-    scenario.open(file).await;
-    {
-        *scenario.client.options.lock().await = Options {
-            bibtex: Some(BibtexOptions {
-                formatting: options,
-            }),
-            latex: None,
-        };
-    }
+    let scenario = create_scenario("formatting/bibtex", file, bibtex_options(options)).await;
+    let params = DocumentFormattingParams {
+        text_document: TextDocumentIdentifier::new(scenario.uri(file).into()),
+        options: formatting_options(),
+    };
+
+    let edits = scenario
+        .server
+        .execute(|svr| svr.formatting(params))
+        .await
+        .unwrap();
+    (scenario, edits)
+}
 
+pub async fn run_bibtex_range(
+    file: &'static str,
+    options: Option<BibtexFormattingOptions>,
+    range: Range,
+) -> (Scenario, Vec<TextEdit>) {
+    let scenario = create_scenario("formatting/bibtex", file, bibtex_options(options)).await;
+    let params = DocumentRangeFormattingParams {
+        text_document: TextDocumentIdentifier::new(scenario.uri(file).into()),
+        range,
+        options: formatting_options(),
+    };
+
+    let edits = scenario
+        .server
+        .execute(|svr| svr.range_formatting(params))
+        .await
+        .unwrap();
+    (scenario, edits)
+}
+
+pub async fn run_latex(
+    file: &'static str,
+    options: Option<LatexFormattingOptions>,
+) -> (Scenario, Vec<TextEdit>) {
+    let options = Options {
+        latex: Some(LatexOptions {
+            formatting: options,
+            ..LatexOptions::default()
+        }),
+        bibtex: None,
+    };
+    let scenario = create_scenario("formatting/latex", file, options).await;
     let params = DocumentFormattingParams {
         text_document: TextDocumentIdentifier::new(scenario.uri(file).into()),
-        options: FormattingOptions {
-            tab_size: 4,
-            insert_spaces: true,
-            properties: HashMap::new(),
-        },
+        options: formatting_options(),
     };
 
     let edits = scenario
@@ -35,3 +65,30 @@ pub async fn run_bibtex(
         .unwrap();
     (scenario, edits)
 }
+
+async fn create_scenario(directory: &str, file: &'static str, options: Options) -> Scenario {
+    let scenario = Scenario::new(directory, false).await;
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+    scenario.open(file).await;
+    {
+        *scenario.client.options.lock().await = options;
+    }
+    scenario
+}
+
+fn bibtex_options(options: Option<BibtexFormattingOptions>) -> Options {
+    Options {
+        bibtex: Some(BibtexOptions {
+            formatting: options,
+        }),
+        latex: None,
+    }
+}
+
+fn formatting_options() -> FormattingOptions {
+    FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        properties: HashMap::new(),
+    }
+}
@@ -6,6 +6,7 @@ use std::fs::remove_dir;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tempfile::{tempdir, TempDir};
+use texlab::cache::DocumentCache;
 use texlab::server::LatexLspServer;
 use texlab_distro::{Distribution, UnknownDistribution};
 use texlab_protocol::*;
@@ -40,7 +41,11 @@ impl Scenario {
         copy_dir(source, directory.path()).unwrap();
 
         let client = Arc::new(MockLspClient::new());
-        let server = LatexLspServer::new(Arc::clone(&client), Arc::clone(&distribution));
+        let server = LatexLspServer::new(
+            Arc::clone(&client),
+            Arc::clone(&distribution),
+            Arc::new(DocumentCache::default()),
+        );
         Self {
             distribution,
             directory,
@@ -50,7 +55,21 @@ impl Scenario {
     }
 
     pub async fn initialize(&self, capabilities: &ClientCapabilities) {
+        self.initialize_folders(capabilities, None).await;
+    }
+
+    pub async fn initialize_folders(
+        &self,
+        capabilities: &ClientCapabilities,
+        folders: Option<&[&str]>,
+    ) {
         let root_uri = Uri::from_file_path(self.directory.path()).unwrap();
+        let workspace_folders = folders.map(|folders| {
+            folders
+                .iter()
+                .map(|name| self.folder(name))
+                .collect::<Vec<_>>()
+        });
         let params = InitializeParams {
             process_id: None,
             root_path: Some(self.directory.path().to_string_lossy().into_owned()),
@@ -58,7 +77,7 @@ impl Scenario {
             initialization_options: None,
             capabilities: capabilities.clone(),
             trace: None,
-            workspace_folders: None,
+            workspace_folders,
         };
 
         self.server
@@ -77,6 +96,13 @@ impl Scenario {
         Uri::from_file_path(path).unwrap()
     }
 
+    pub fn folder(&self, name: &str) -> WorkspaceFolder {
+        WorkspaceFolder {
+            uri: self.uri(name).into(),
+            name: name.to_owned(),
+        }
+    }
+
     pub async fn read(&self, name: &'static str) -> String {
         let mut path = self.directory.path().to_owned();
         path.push(name);
@@ -1,7 +1,7 @@
 use std::time::SystemTime;
 use texlab_distro::{Language, Resolver};
 use texlab_protocol::*;
-use texlab_syntax::{SyntaxTree, SyntaxTreeInput};
+use texlab_syntax::{edit_text, SyntaxTree, SyntaxTreeInput, SyntaxTreeSnapshot};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Document {
@@ -35,6 +35,55 @@ impl Document {
         }
     }
 
+    pub fn restore(
+        uri: Uri,
+        text: String,
+        snapshot: SyntaxTreeSnapshot,
+        modified: SystemTime,
+        options: &Options,
+        resolver: &Resolver,
+    ) -> Self {
+        let input = SyntaxTreeInput {
+            options,
+            resolver,
+            uri: &uri,
+            text: &text,
+            language: snapshot.language(),
+        };
+        let tree = SyntaxTree::restore(input, snapshot);
+        Self {
+            uri,
+            text,
+            tree,
+            modified,
+        }
+    }
+
+    pub fn edit(
+        &self,
+        range: Range,
+        replacement: &str,
+        options: &Options,
+        resolver: &Resolver,
+    ) -> Self {
+        let mut text = self.text.clone();
+        edit_text(&mut text, range, replacement);
+        let input = SyntaxTreeInput {
+            options,
+            resolver,
+            uri: &self.uri,
+            text: &text,
+            language: self.tree.language(),
+        };
+        let tree = self.tree.reparse(input, range, replacement);
+        Self {
+            uri: self.uri.clone(),
+            text,
+            tree,
+            modified: SystemTime::now(),
+        }
+    }
+
     pub fn is_file(&self) -> bool {
         self.uri.scheme() == "file"
     }
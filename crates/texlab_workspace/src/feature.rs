@@ -2,6 +2,7 @@ use super::document::Document;
 use super::workspace::{TestWorkspaceBuilder, Workspace};
 use futures::executor::block_on;
 use futures_boxed::boxed;
+use std::collections::HashMap;
 use std::sync::Arc;
 use texlab_distro::{Distribution, UnknownDistribution};
 use texlab_protocol::*;
@@ -128,6 +129,7 @@ pub struct FeatureSpec {
     pub files: Vec<FeatureSpecFile>,
     pub main_file: &'static str,
     pub position: Position,
+    pub range: Range,
     pub new_name: &'static str,
     pub include_declaration: bool,
     pub client_capabilities: ClientCapabilities,
@@ -141,6 +143,7 @@ impl Default for FeatureSpec {
             files: Vec::new(),
             main_file: "",
             position: Position::new(0, 0),
+            range: Range::new(Position::new(0, 0), Position::new(0, 0)),
             new_name: "",
             include_declaration: false,
             client_capabilities: ClientCapabilities::default(),
@@ -268,6 +271,83 @@ impl Into<FeatureRequest<DocumentSymbolParams>> for FeatureSpec {
     }
 }
 
+impl Into<FeatureRequest<CodeActionParams>> for FeatureSpec {
+    fn into(self) -> FeatureRequest<CodeActionParams> {
+        let params = CodeActionParams {
+            text_document: self.identifier(),
+            range: self.range,
+            context: CodeActionContext {
+                diagnostics: Vec::new(),
+                only: None,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        self.request(params)
+    }
+}
+
+impl Into<FeatureRequest<CodeLensParams>> for FeatureSpec {
+    fn into(self) -> FeatureRequest<CodeLensParams> {
+        let params = CodeLensParams {
+            text_document: self.identifier(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        self.request(params)
+    }
+}
+
+impl Into<FeatureRequest<SemanticTokensParams>> for FeatureSpec {
+    fn into(self) -> FeatureRequest<SemanticTokensParams> {
+        let params = SemanticTokensParams {
+            text_document: self.identifier(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        self.request(params)
+    }
+}
+
+impl Into<FeatureRequest<SelectionRangeParams>> for FeatureSpec {
+    fn into(self) -> FeatureRequest<SelectionRangeParams> {
+        let params = SelectionRangeParams {
+            text_document: self.identifier(),
+            positions: vec![self.position],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        self.request(params)
+    }
+}
+
+impl Into<FeatureRequest<DocumentOnTypeFormattingParams>> for FeatureSpec {
+    fn into(self) -> FeatureRequest<DocumentOnTypeFormattingParams> {
+        let params = DocumentOnTypeFormattingParams {
+            text_document_position: TextDocumentPositionParams::new(
+                self.identifier(),
+                self.position,
+            ),
+            ch: "}".to_owned(),
+            options: FormattingOptions {
+                tab_size: 4,
+                insert_spaces: true,
+                properties: HashMap::new(),
+            },
+        };
+        self.request(params)
+    }
+}
+
+impl Into<FeatureRequest<TodoParams>> for FeatureSpec {
+    fn into(self) -> FeatureRequest<TodoParams> {
+        let params = TodoParams {
+            text_document: self.identifier(),
+        };
+        self.request(params)
+    }
+}
+
 pub fn test_feature<F, P, O, S>(provider: F, spec: S) -> O
 where
     F: FeatureProvider<Params = P, Output = O>,
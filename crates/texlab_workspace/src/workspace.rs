@@ -1,78 +1,131 @@
 use super::components::COMPONENT_DATABASE;
 use super::document::Document;
 use path_clean::PathClean;
-use petgraph::visit::Dfs;
-use petgraph::Graph;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::ffi::OsStr;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use texlab_distro::{Language, Resolver};
 use texlab_protocol::*;
 use texlab_syntax::*;
 
+type Index<K> = Arc<HashMap<K, Vec<Uri>>>;
+
+/// The indexes are shared between clones of the workspace
+/// and only copied once their contents change.
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct Workspace {
-    pub documents: Vec<Arc<Document>>,
+    documents: Arc<BTreeMap<u64, Arc<Document>>>,
+    keys_by_uri: Arc<HashMap<Uri, u64>>,
+    next_key: u64,
+    parents_by_target: Index<Uri>,
+    parents_by_stem: Index<String>,
+    label_index: Index<String>,
+    citation_index: Index<String>,
+    entry_index: Index<String>,
+    related_cache: RelatedCache,
 }
 
 impl Workspace {
     pub fn new() -> Self {
-        Self {
-            documents: Vec::new(),
-        }
+        Self::default()
+    }
+
+    /// Returns the documents in the order in which they were first added.
+    pub fn documents(&self) -> impl Iterator<Item = &Arc<Document>> {
+        self.documents.values()
     }
 
     pub fn find(&self, uri: &Uri) -> Option<Arc<Document>> {
-        self.documents
-            .iter()
-            .find(|document| &document.uri == uri)
-            .map(|document| Arc::clone(&document))
+        self.keys_by_uri
+            .get(uri)
+            .map(|key| Arc::clone(&self.documents[key]))
     }
 
-    pub fn related_documents(&self, uri: &Uri, options: &Options) -> Vec<Arc<Document>> {
-        let mut graph = Graph::new_undirected();
-        let mut indices_by_uri = HashMap::new();
-        for document in &self.documents {
-            indices_by_uri.insert(&document.uri, graph.add_node(document));
-        }
+    /// Adds the document to the workspace or replaces the document with the same URI in place.
+    pub fn insert(&mut self, document: Arc<Document>) {
+        let old_document = self.find(&document.uri);
+        self.reindex(&document.uri, old_document.as_deref(), Some(&document));
+        let key = match self.keys_by_uri.get(&document.uri) {
+            Some(key) => *key,
+            None => {
+                let key = self.next_key;
+                self.next_key += 1;
+                Arc::make_mut(&mut self.keys_by_uri).insert(document.uri.clone(), key);
+                key
+            }
+        };
+        Arc::make_mut(&mut self.documents).insert(key, document);
+        self.related_cache = RelatedCache::default();
+    }
 
-        for parent in self.documents.iter().filter(|doc| doc.is_file()) {
-            if let SyntaxTree::Latex(tree) = &parent.tree {
-                for include in &tree.includes {
-                    for targets in &include.all_targets {
-                        for target in targets {
-                            if let Some(ref child) = self.find(target) {
-                                graph.add_edge(
-                                    indices_by_uri[&parent.uri],
-                                    indices_by_uri[&child.uri],
-                                    (),
-                                );
-                            }
-                        }
-                    }
-                }
+    pub fn remove(&mut self, uri: &Uri) -> Option<Arc<Document>> {
+        let key = *self.keys_by_uri.get(uri)?;
+        Arc::make_mut(&mut self.keys_by_uri).remove(uri);
+        let document = Arc::make_mut(&mut self.documents).remove(&key).unwrap();
+        self.reindex(uri, Some(&document), None);
+        self.related_cache = RelatedCache::default();
+        Some(document)
+    }
 
-                if let Some(child) = Self::aux_path(&parent.uri, options)
-                    .and_then(|aux_path| Uri::from_file_path(aux_path).ok())
-                    .and_then(|aux_uri| self.find(&aux_uri))
-                {
-                    graph.add_edge(indices_by_uri[&parent.uri], indices_by_uri[&child.uri], ());
-                }
+    /// Returns the documents that define or reference a label with the given name.
+    pub fn label_documents(&self, name: &str) -> &[Uri] {
+        Self::lookup(&self.label_index, name)
+    }
+
+    /// Returns the documents that cite the given key.
+    pub fn citation_documents(&self, key: &str) -> &[Uri] {
+        Self::lookup(&self.citation_index, key)
+    }
+
+    /// Returns the bibliographies that define an entry with the given key.
+    pub fn entry_documents(&self, key: &str) -> &[Uri] {
+        Self::lookup(&self.entry_index, key)
+    }
+
+    /// Returns the documents that are connected to the given document in the include graph,
+    /// starting with the document itself.
+    /// The result is cached until the workspace changes.
+    pub fn related_documents(&self, uri: &Uri, options: &Options) -> Vec<Arc<Document>> {
+        let mut cache = self.related_cache.0.lock().unwrap();
+        if let Some((cached_options, documents)) = cache.get(uri) {
+            if cached_options == options {
+                return documents.clone();
             }
         }
 
+        let documents = self.find_related_documents(uri, options);
+        cache.insert(uri.clone(), (options.clone(), documents.clone()));
+        documents
+    }
+
+    fn find_related_documents(&self, uri: &Uri, options: &Options) -> Vec<Arc<Document>> {
         let mut documents = Vec::new();
-        if self.find(uri).is_some() {
-            let mut dfs = Dfs::new(&graph, indices_by_uri[uri]);
-            while let Some(index) = dfs.next(&graph) {
-                documents.push(Arc::clone(&graph.node_weight(index).unwrap()));
+        let mut visited = HashSet::new();
+        let mut stack: Vec<Arc<Document>> = self.find(uri).into_iter().collect();
+        while let Some(document) = stack.pop() {
+            if !visited.insert(document.uri.clone()) {
+                continue;
+            }
+
+            for neighbor in self.neighbors(&document, options) {
+                if !visited.contains(&neighbor.uri) {
+                    stack.push(neighbor);
+                }
             }
+            documents.push(document);
         }
         documents
     }
 
     pub fn find_parent(&self, uri: &Uri, options: &Options) -> Option<Arc<Document>> {
+        if let Some(root) = self.find_magic_root(uri) {
+            return Some(root);
+        }
+
         for document in self.related_documents(uri, options) {
             if let SyntaxTree::Latex(tree) = &document.tree {
                 if tree.env.is_standalone {
@@ -83,9 +136,17 @@ impl Workspace {
         None
     }
 
+    fn find_magic_root(&self, uri: &Uri) -> Option<Arc<Document>> {
+        let document = self.find(uri)?;
+        match &document.tree {
+            SyntaxTree::Latex(tree) => self.find(tree.magic.root.as_ref()?),
+            SyntaxTree::Bibtex(_) => None,
+        }
+    }
+
     pub fn unresolved_includes(&self, options: &Options) -> Vec<PathBuf> {
         let mut includes = Vec::new();
-        for document in &self.documents {
+        for document in self.documents() {
             if let SyntaxTree::Latex(tree) = &document.tree {
                 for include in &tree.includes {
                     match include.kind {
@@ -120,6 +181,16 @@ impl Workspace {
                     }
                 }
 
+                if let Some(root) = &tree.magic.root {
+                    if self.find(root).is_none() {
+                        if let Ok(path) = root.to_file_path() {
+                            if path.exists() {
+                                includes.push(path);
+                            }
+                        }
+                    }
+                }
+
                 if let Some(aux_path) = Self::aux_path(&document.uri, options) {
                     if self
                         .find(&Uri::from_file_path(&aux_path).unwrap())
@@ -130,9 +201,100 @@ impl Workspace {
                 }
             }
         }
+        includes.retain(|path| !options.is_excluded(path));
         includes
     }
 
+    /// Returns the neighbors of the document in the include graph.
+    /// The children come first, followed by the parents, both in reverse order of discovery.
+    fn neighbors(&self, document: &Document, options: &Options) -> Vec<Arc<Document>> {
+        let mut neighbors: Vec<_> = self.children(document, options).into_iter().rev().collect();
+
+        let mut parents = Vec::new();
+        if let Some(parent_uris) = self.parents_by_target.get(&document.uri) {
+            parents.extend(parent_uris.iter().filter_map(|uri| self.find(uri)));
+        }
+
+        for parent in self.aux_candidates(document) {
+            let aux_uri = Self::aux_path(&parent.uri, options)
+                .and_then(|aux_path| Uri::from_file_path(aux_path).ok());
+            if aux_uri.as_ref() == Some(&document.uri) {
+                parents.push(parent);
+            }
+        }
+
+        parents.retain(|parent| parent.uri != document.uri);
+        parents.sort_by_key(|parent| self.keys_by_uri[&parent.uri]);
+        neighbors.extend(parents.into_iter().rev());
+        neighbors
+    }
+
+    fn children(&self, document: &Document, options: &Options) -> Vec<Arc<Document>> {
+        let mut children = Vec::new();
+        if !document.is_file() {
+            return children;
+        }
+
+        if let SyntaxTree::Latex(tree) = &document.tree {
+            for include in &tree.includes {
+                for targets in &include.all_targets {
+                    children.extend(targets.iter().filter_map(|target| self.find(target)));
+                }
+            }
+
+            if let Some(child) = Self::aux_path(&document.uri, options)
+                .and_then(|aux_path| Uri::from_file_path(aux_path).ok())
+                .and_then(|aux_uri| self.find(&aux_uri))
+            {
+                children.push(child);
+            }
+        }
+        children
+    }
+
+    /// Returns the LaTeX documents whose auxiliary file could be the given document.
+    fn aux_candidates(&self, document: &Document) -> Vec<Arc<Document>> {
+        let path = match document.uri.to_file_path() {
+            Ok(path) => path,
+            Err(()) => return Vec::new(),
+        };
+
+        if path.extension().and_then(OsStr::to_str) != Some("aux") {
+            return Vec::new();
+        }
+
+        file_stem(&path)
+            .and_then(|stem| self.parents_by_stem.get(stem))
+            .into_iter()
+            .flatten()
+            .filter_map(|uri| self.find(uri))
+            .collect()
+    }
+
+    fn reindex(&mut self, uri: &Uri, old_document: Option<&Document>, document: Option<&Document>) {
+        let old_keys = old_document.map(IndexKeys::new).unwrap_or_default();
+        let keys = document.map(IndexKeys::new).unwrap_or_default();
+        update_index(
+            &mut self.parents_by_target,
+            uri,
+            old_keys.targets,
+            keys.targets,
+        );
+        update_index(&mut self.parents_by_stem, uri, old_keys.stems, keys.stems);
+        update_index(&mut self.label_index, uri, old_keys.labels, keys.labels);
+        update_index(
+            &mut self.citation_index,
+            uri,
+            old_keys.citations,
+            keys.citations,
+        );
+        update_index(&mut self.entry_index, uri, old_keys.entries, keys.entries);
+    }
+
+    fn lookup<'a>(index: &'a Index<String>, key: &str) -> &'a [Uri] {
+        index.get(key).map(Vec::as_slice).unwrap_or_default()
+    }
+
     fn aux_path(tex_uri: &Uri, options: &Options) -> Option<PathBuf> {
         let tex_path = tex_uri.to_file_path().ok()?;
         let aux_path = PathBuf::from(
@@ -146,6 +308,116 @@ impl Workspace {
     }
 }
 
+impl FromIterator<Arc<Document>> for Workspace {
+    fn from_iter<I: IntoIterator<Item = Arc<Document>>>(documents: I) -> Self {
+        let mut workspace = Self::new();
+        for document in documents {
+            workspace.insert(document);
+        }
+        workspace
+    }
+}
+
+/// The keys under which a document is stored in the indexes of the workspace.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct IndexKeys {
+    targets: Vec<Uri>,
+    stems: Vec<String>,
+    labels: Vec<String>,
+    citations: Vec<String>,
+    entries: Vec<String>,
+}
+
+impl IndexKeys {
+    fn new(document: &Document) -> Self {
+        let mut keys = Self::default();
+        match &document.tree {
+            SyntaxTree::Latex(tree) => {
+                if document.is_file() {
+                    for include in &tree.includes {
+                        keys.targets
+                            .extend(include.all_targets.iter().flatten().cloned());
+                    }
+
+                    let path = document.uri.to_file_path().ok();
+                    keys.stems
+                        .extend(path.as_deref().and_then(file_stem).map(ToOwned::to_owned));
+                }
+
+                for label in &tree.structure.labels {
+                    keys.labels
+                        .extend(label.names().into_iter().map(|name| name.text().to_owned()));
+                }
+
+                for citation in &tree.citations {
+                    keys.citations
+                        .extend(citation.keys().into_iter().map(|key| key.text().to_owned()));
+                }
+            }
+            SyntaxTree::Bibtex(tree) => {
+                keys.entries.extend(
+                    tree.entries()
+                        .into_iter()
+                        .filter_map(|entry| entry.key.as_ref())
+                        .map(|key| key.text().to_owned()),
+                );
+            }
+        }
+        keys
+    }
+}
+
+/// Memoizes the related documents of a workspace.
+/// Clones of the workspace start with an empty cache.
+#[derive(Debug, Default)]
+struct RelatedCache(Mutex<HashMap<Uri, (Options, Vec<Arc<Document>>)>>);
+
+impl Clone for RelatedCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for RelatedCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for RelatedCache {}
+
+fn update_index<K: Eq + Hash + Clone>(
+    index: &mut Index<K>,
+    uri: &Uri,
+    old_keys: Vec<K>,
+    keys: Vec<K>,
+) {
+    if old_keys == keys {
+        return;
+    }
+
+    let index = Arc::make_mut(index);
+    for key in old_keys {
+        if let Some(uris) = index.get_mut(&key) {
+            if let Some(position) = uris.iter().position(|other| other == uri) {
+                uris.remove(position);
+            }
+
+            if uris.is_empty() {
+                index.remove(&key);
+            }
+        }
+    }
+
+    for key in keys {
+        index.entry(key).or_default().push(uri.clone());
+    }
+}
+
+fn file_stem(path: &Path) -> Option<&str> {
+    path.file_stem().and_then(OsStr::to_str)
+}
+
 #[derive(Debug, Default)]
 pub struct TestWorkspaceBuilder {
     pub workspace: Workspace,
@@ -163,7 +435,7 @@ impl TestWorkspaceBuilder {
         let language = Language::by_extension(path.extension().unwrap().to_str().unwrap()).unwrap();
         let uri = Uri::from_file_path(path).unwrap();
         let document = Document::parse(uri.clone(), text.to_owned(), language, &options, &resolver);
-        self.workspace.documents.push(Arc::new(document));
+        self.workspace.insert(Arc::new(document));
         uri
     }
 }
@@ -287,6 +559,62 @@ mod tests {
         verify_documents(vec![uri1, uri2], documents);
     }
 
+    #[test]
+    fn indexes() {
+        let mut builder = TestWorkspaceBuilder::new();
+        let uri1 = builder.add_document("foo.tex", "\\label{foo}\\cite{bar}");
+        let uri2 = builder.add_document("bar.bib", "@article{bar,}");
+        let uri3 = builder.add_document("baz.tex", "\\ref{foo}");
+        let workspace = builder.workspace;
+        assert_eq!(workspace.label_documents("foo"), &[uri1.clone(), uri3][..]);
+        assert_eq!(workspace.citation_documents("bar"), &[uri1][..]);
+        assert_eq!(workspace.entry_documents("bar"), &[uri2][..]);
+        assert!(workspace.label_documents("bar").is_empty());
+    }
+
+    #[test]
+    fn indexes_replace_document() {
+        let mut builder = TestWorkspaceBuilder::new();
+        let uri1 = builder.add_document("foo.tex", "\\label{foo}");
+        let uri2 = builder.add_document("foo.tex", "\\label{bar}");
+        let workspace = builder.workspace;
+        assert_eq!(workspace.documents().count(), 1);
+        assert!(workspace.label_documents("foo").is_empty());
+        assert_eq!(workspace.label_documents("bar"), &[uri1][..]);
+        assert_eq!(workspace.find(&uri2).unwrap().text, "\\label{bar}");
+    }
+
+    #[test]
+    fn indexes_shared_between_clones() {
+        let mut builder = TestWorkspaceBuilder::new();
+        builder.add_document("foo.tex", "\\label{foo}");
+        let old_workspace = builder.workspace.clone();
+        let uri = builder.add_document("foo.tex", "\\label{foo} bar");
+        let workspace = builder.workspace;
+        assert!(Arc::ptr_eq(
+            &old_workspace.label_index,
+            &workspace.label_index
+        ));
+        assert!(!Arc::ptr_eq(&old_workspace.documents, &workspace.documents));
+        assert_eq!(workspace.find(&uri).unwrap().text, "\\label{foo} bar");
+    }
+
+    #[test]
+    fn remove_document() {
+        let mut builder = TestWorkspaceBuilder::new();
+        let uri1 = builder.add_document("foo.tex", "\\include{bar}");
+        let uri2 = builder.add_document("bar.tex", "\\label{foo}");
+        let uri3 = builder.add_document("baz.tex", "");
+        let mut workspace = builder.workspace;
+        workspace.remove(&uri1);
+        assert_eq!(workspace.find(&uri1), None);
+        assert_eq!(workspace.find(&uri3).unwrap().uri, uri3);
+        verify_documents(
+            vec![uri2.clone()],
+            workspace.related_documents(&uri2, &Options::default()),
+        );
+    }
+
     #[test]
     fn find_parent() {
         let mut builder = TestWorkspaceBuilder::new();
@@ -300,6 +628,19 @@ mod tests {
         assert_eq!(uri2, document.uri);
     }
 
+    #[test]
+    fn find_parent_magic_root() {
+        let mut builder = TestWorkspaceBuilder::new();
+        let uri1 = builder.add_document("foo.tex", "% !TEX root = baz.tex");
+        builder.add_document("bar.tex", "\\begin{document}\\include{foo}\\end{document}");
+        let uri3 = builder.add_document("baz.tex", "\\begin{document}\\end{document}");
+        let document = builder
+            .workspace
+            .find_parent(&uri1, &Options::default())
+            .unwrap();
+        assert_eq!(uri3, document.uri);
+    }
+
     #[test]
     fn find_parent_no_parent() {
         let mut builder = TestWorkspaceBuilder::new();
@@ -1,4 +1,5 @@
 use std::mem;
+use std::path::PathBuf;
 use std::sync::Mutex;
 use texlab_protocol::{ProgressToken, Uri};
 
@@ -11,10 +12,12 @@ pub enum LintReason {
 #[derive(Debug, PartialEq, Clone)]
 pub enum Action {
     RegisterCapabilities,
+    RestoreCache,
     LoadDistribution,
     LoadConfiguration,
     UpdateConfiguration(serde_json::Value),
     DetectRoot(Uri),
+    LoadFolder(PathBuf),
     PublishDiagnostics,
     RunLinter(Uri, LintReason),
     Build(Uri),
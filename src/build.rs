@@ -1,24 +1,28 @@
+use crate::progress;
 use futures::future::{AbortHandle, Abortable, Aborted};
 use futures::lock::Mutex;
 use futures::prelude::*;
 use futures::stream;
 use futures_boxed::boxed;
+use log::warn;
 use std::collections::HashMap;
 use std::io;
 use std::path::Path;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use texlab_protocol::*;
+use texlab_syntax::*;
 use texlab_workspace::*;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
-use uuid::Uuid;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug)]
 pub struct BuildProvider<C> {
     pub client: Arc<C>,
     pub options: LatexOptions,
     pub token: ProgressToken,
+    has_progress: AtomicBool,
 }
 
 impl<C> BuildProvider<C>
@@ -29,11 +33,12 @@ where
         Self {
             client,
             options,
-            token: ProgressToken::String(format!("texlab-build-{}", Uuid::new_v4())),
+            token: progress::token("build"),
+            has_progress: AtomicBool::new(false),
         }
     }
 
-    async fn build<'a>(&'a self, path: &'a Path) -> io::Result<bool> {
+    async fn build<'a>(&'a self, path: &'a Path, magic: &'a LatexMagicInfo) -> io::Result<bool> {
         let build_options = self
             .options
             .build
@@ -49,11 +54,14 @@ where
             .or_else(|| path.parent())
             .unwrap();
 
-        let mut args = Vec::new();
-        args.append(&mut build_options.args());
+        let executable = build_options.executable();
+        let mut args = build_options.args();
+        if is_latexmk(&executable) {
+            args = latexmk_args(args, magic);
+        }
         args.push(path.to_string_lossy().into_owned());
 
-        let mut process = Command::new(build_options.executable())
+        let mut process = Command::new(executable)
             .args(args)
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
@@ -93,30 +101,27 @@ where
             .or_else(|| request.workspace().find(&request.document().uri))
             .unwrap();
 
+        let magic = match &document.tree {
+            SyntaxTree::Latex(tree) => tree.magic.clone(),
+            SyntaxTree::Bibtex(_) => LatexMagicInfo::default(),
+        };
+
         match document.uri.to_file_path() {
             Ok(path) => {
                 if request.client_capabilities.has_work_done_progress_support() {
-                    let params = WorkDoneProgressCreateParams {
-                        token: self.token.clone(),
-                    };
-                    self.client.work_done_progress_create(params).await.unwrap();
-
                     let title = path.file_name().unwrap().to_string_lossy().into_owned();
-                    let params = ProgressParams {
-                        token: self.token.clone(),
-                        value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(
-                            WorkDoneProgressBegin {
-                                title,
-                                cancellable: Some(true),
-                                message: Some("Building".into()),
-                                percentage: None,
-                            },
-                        )),
+                    let begin = WorkDoneProgressBegin {
+                        title,
+                        cancellable: Some(true),
+                        message: Some("Building".into()),
+                        percentage: None,
                     };
-                    self.client.progress(params).await;
+                    let has_progress =
+                        progress::begin(self.client.as_ref(), self.token.clone(), begin).await;
+                    self.has_progress.store(has_progress, Ordering::SeqCst);
                 }
 
-                let status = match self.build(&path).await {
+                let status = match self.build(&path, &magic).await {
                     Ok(true) => BuildStatus::Success,
                     Ok(false) => BuildStatus::Error,
                     Err(_) => BuildStatus::Failure,
@@ -131,6 +136,76 @@ where
     }
 }
 
+const LATEXMK_ENGINE_FLAGS: &[&str] = &[
+    "-pdf",
+    "-pdfdvi",
+    "-pdfps",
+    "-pdfxe",
+    "-pdflua",
+    "-xelatex",
+    "-lualatex",
+    "-dvi",
+    "-ps",
+];
+
+fn is_latexmk(executable: &str) -> bool {
+    Path::new(executable)
+        .file_stem()
+        .map_or(false, |stem| stem == "latexmk")
+}
+
+/// The engines that can be selected with the `% !TEX program` magic comment.
+/// Only the listed programs are accepted because the comment is taken from the document.
+const LATEXMK_TEX_PROGRAMS: &[(&str, &str)] = &[
+    ("pdflatex", "-pdf"),
+    ("xelatex", "-xelatex"),
+    ("lualatex", "-lualatex"),
+    ("latex", "-dvi"),
+];
+
+/// The bibliography processors that can be selected with the `% !BIB program` magic comment.
+const LATEXMK_BIB_PROGRAMS: &[(&str, &str)] = &[
+    ("biber", "$biber=q/biber %O %S/"),
+    ("bibtex", "$bibtex=q/bibtex %O %B/"),
+    ("bibtex8", "$bibtex=q/bibtex8 %O %B/"),
+    ("upbibtex", "$bibtex=q/upbibtex %O %B/"),
+];
+
+/// Selects the engines requested by the `% !TEX program` and `% !BIB program` magic comments.
+/// Unknown programs are ignored.
+fn latexmk_args(mut args: Vec<String>, magic: &LatexMagicInfo) -> Vec<String> {
+    let mut engine_args = Vec::new();
+    if let Some(program) = &magic.program {
+        match find_program(LATEXMK_TEX_PROGRAMS, program) {
+            Some(flag) => {
+                args.retain(|arg| !LATEXMK_ENGINE_FLAGS.contains(&arg.as_str()));
+                engine_args.push(flag.to_owned());
+            }
+            None => warn!("Ignoring unknown TeX program: {}", program),
+        }
+    }
+
+    if let Some(program) = &magic.bib_program {
+        match find_program(LATEXMK_BIB_PROGRAMS, program) {
+            Some(code) => {
+                engine_args.push("-e".to_owned());
+                engine_args.push(code.to_owned());
+            }
+            None => warn!("Ignoring unknown BibTeX program: {}", program),
+        }
+    }
+
+    engine_args.append(&mut args);
+    engine_args
+}
+
+fn find_program(programs: &[(&str, &'static str)], program: &str) -> Option<&'static str> {
+    programs
+        .iter()
+        .find(|(name, _)| *name == program)
+        .map(|(_, value)| *value)
+}
+
 pub struct BuildManager<C> {
     handles_by_token: Mutex<HashMap<ProgressToken, AbortHandle>>,
     client: Arc<C>,
@@ -166,14 +241,8 @@ where
             },
         };
 
-        if request.client_capabilities.has_work_done_progress_support() {
-            let params = ProgressParams {
-                token: provider.token.clone(),
-                value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(WorkDoneProgressEnd {
-                    message: None,
-                })),
-            };
-            self.client.progress(params).await;
+        if provider.has_progress.load(Ordering::SeqCst) {
+            progress::end(self.client.as_ref(), provider.token.clone()).await;
         }
 
         {
@@ -195,3 +264,60 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_args() -> Vec<String> {
+        LatexBuildOptions::default().args()
+    }
+
+    #[test]
+    fn latexmk_args_program() {
+        let magic = LatexMagicInfo {
+            program: Some("xelatex".into()),
+            ..LatexMagicInfo::default()
+        };
+        assert_eq!(
+            latexmk_args(default_args(), &magic),
+            vec!["-xelatex", "-interaction=nonstopmode", "-synctex=1"]
+        );
+    }
+
+    #[test]
+    fn latexmk_args_unknown_program() {
+        let magic = LatexMagicInfo {
+            program: Some("pdflatex -shell-escape".into()),
+            bib_program: Some("biber/; system q/rm/; q/".into()),
+            ..LatexMagicInfo::default()
+        };
+        assert_eq!(latexmk_args(default_args(), &magic), default_args());
+    }
+
+    #[test]
+    fn latexmk_args_bib_program() {
+        let magic = LatexMagicInfo {
+            bib_program: Some("biber".into()),
+            ..LatexMagicInfo::default()
+        };
+        assert_eq!(
+            latexmk_args(default_args(), &magic),
+            vec![
+                "-e",
+                "$biber=q/biber %O %S/",
+                "-pdf",
+                "-interaction=nonstopmode",
+                "-synctex=1"
+            ]
+        );
+    }
+
+    #[test]
+    fn latexmk_args_default() {
+        assert_eq!(
+            latexmk_args(default_args(), &LatexMagicInfo::default()),
+            default_args()
+        );
+    }
+}
@@ -0,0 +1,570 @@
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use texlab_distro::{DistributionKind, Resolver};
+use texlab_syntax::SyntaxTreeSnapshot;
+
+const DOCUMENTS_DIRECTORY: &str = "documents";
+
+const INDEX_FILE: &str = "index.json";
+
+/// The single file that held all documents in previous versions.
+const LEGACY_DOCUMENTS_FILE: &str = "documents.json";
+
+const RESOLVER_FILE: &str = "resolver.json";
+
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Documents that have not been used for this long are removed from the cache.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// The number of documents that are kept. The least recently used documents are removed first.
+const MAX_ENTRIES: usize = 2000;
+
+/// The precision of the last use, so that looking up a document does not always require a save.
+const LAST_USED_RESOLUTION: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntry {
+    pub modified: SystemTime,
+    pub hash: u64,
+    pub text: String,
+    pub tree: SyntaxTreeSnapshot,
+}
+
+/// Describes a cached document without its content.
+/// The content is stored in a separate file that is only read on demand.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexEntry {
+    modified: SystemTime,
+    hash: u64,
+    last_used: SystemTime,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolverEntry {
+    kind: DistributionKind,
+    resolver: Resolver,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    index: Option<HashMap<PathBuf, IndexEntry>>,
+    entries: HashMap<PathBuf, CacheEntry>,
+    changed_paths: HashSet<PathBuf>,
+    removed_paths: HashSet<PathBuf>,
+    is_dirty: bool,
+    last_saved: Option<Instant>,
+}
+
+impl CacheState {
+    fn remove(&mut self, path: &Path) {
+        if let Some(index) = &mut self.index {
+            index.remove(path);
+        }
+        self.entries.remove(path);
+        self.changed_paths.remove(path);
+        self.removed_paths.insert(path.to_owned());
+        self.is_dirty = true;
+    }
+
+    /// Remembers that the document has been used.
+    fn touch(&mut self, path: &Path) {
+        let entry = match self.index.as_mut().and_then(|index| index.get_mut(path)) {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        let now = SystemTime::now();
+        let is_outdated = now
+            .duration_since(entry.last_used)
+            .map_or(true, |age| age >= LAST_USED_RESOLUTION);
+        if is_outdated {
+            entry.last_used = now;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Drops the documents that have not been used for a long time
+    /// and the least recently used documents that exceed the limit.
+    fn prune(&mut self) {
+        let index = match &self.index {
+            Some(index) => index,
+            None => return,
+        };
+
+        let now = SystemTime::now();
+        let mut paths_by_last_use: Vec<_> = index
+            .iter()
+            .map(|(path, entry)| (entry.last_used, path.clone()))
+            .collect();
+        paths_by_last_use.sort();
+        paths_by_last_use.reverse();
+
+        let expired_paths: Vec<_> = paths_by_last_use
+            .into_iter()
+            .enumerate()
+            .filter(|(i, (last_used, _))| {
+                *i >= MAX_ENTRIES
+                    || now
+                        .duration_since(*last_used)
+                        .map_or(false, |age| age > MAX_AGE)
+            })
+            .map(|(_, (_, path))| path)
+            .collect();
+
+        for path in expired_paths {
+            self.remove(&path);
+        }
+    }
+}
+
+/// Persists parsed documents and the file database of the distribution across sessions.
+/// Every document is stored in its own file, next to an index of all documents.
+/// Without a directory, the documents are only kept in memory.
+#[derive(Debug, Default)]
+pub struct DocumentCache {
+    directory: Option<PathBuf>,
+    state: Mutex<CacheState>,
+    file_lock: Mutex<()>,
+}
+
+impl DocumentCache {
+    pub fn new(directory: Option<PathBuf>) -> Self {
+        Self {
+            directory,
+            state: Mutex::default(),
+            file_lock: Mutex::default(),
+        }
+    }
+
+    /// Returns `$XDG_CACHE_HOME/texlab` or `~/.cache/texlab` as a fallback.
+    pub fn default_directory() -> Option<PathBuf> {
+        env::var_os("XDG_CACHE_HOME")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .map(|path| path.join("texlab"))
+    }
+
+    /// Looks up the entry of the given file.
+    /// The entry is only returned if the file still has the same modification time
+    /// or, failing that, the same content.
+    pub fn get(&self, path: &Path) -> Option<CacheEntry> {
+        let modified = fs::metadata(path).and_then(|data| data.modified()).ok();
+        let mut state = self.state.lock().unwrap();
+        let index = self.load_index(&mut state);
+        let index_entry = index.get(path)?.clone();
+        if modified != Some(index_entry.modified) {
+            match (modified, fs::read_to_string(path)) {
+                (Some(modified), Ok(text)) if hash(&text) == index_entry.hash => {
+                    index.get_mut(path).unwrap().modified = modified;
+                    if let Some(entry) = state.entries.get_mut(path) {
+                        entry.modified = modified;
+                    }
+                    state.is_dirty = true;
+                }
+                _ => {
+                    state.remove(path);
+                    return None;
+                }
+            }
+        }
+
+        let entry = self.load_entry(&mut state, path)?;
+        state.touch(path);
+        Some(entry)
+    }
+
+    pub fn insert(
+        &self,
+        path: PathBuf,
+        modified: SystemTime,
+        text: String,
+        tree: SyntaxTreeSnapshot,
+    ) {
+        let hash = hash(&text);
+        let entry = CacheEntry {
+            modified,
+            hash,
+            text,
+            tree,
+        };
+        let index_entry = IndexEntry {
+            modified,
+            hash,
+            last_used: SystemTime::now(),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        self.load_index(&mut state)
+            .insert(path.clone(), index_entry);
+        state.entries.insert(path.clone(), entry);
+        state.removed_paths.remove(&path);
+        state.changed_paths.insert(path);
+        state.is_dirty = true;
+    }
+
+    /// Returns the entries of all files inside the given directory without comparing
+    /// their contents. Entries of files that no longer exist are dropped.
+    pub fn entries_in(&self, directory: &Path) -> Vec<(PathBuf, CacheEntry)> {
+        let mut state = self.state.lock().unwrap();
+        let paths: Vec<_> = self
+            .load_index(&mut state)
+            .keys()
+            .filter(|path| path.starts_with(directory))
+            .cloned()
+            .collect();
+
+        let mut entries = Vec::new();
+        for path in paths {
+            if !path.exists() {
+                state.remove(&path);
+                continue;
+            }
+
+            if let Some(entry) = self.load_entry(&mut state, &path) {
+                state.touch(&path);
+                entries.push((path, entry));
+            }
+        }
+        entries
+    }
+
+    /// Checks whether there are unsaved changes and the last save is long enough ago.
+    pub fn is_save_due(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.is_dirty
+            && state
+                .last_saved
+                .map_or(true, |last_saved| last_saved.elapsed() >= SAVE_INTERVAL)
+    }
+
+    /// Writes the changed documents and the index to disk.
+    /// The state is only locked while the changes are collected.
+    pub fn save(&self) {
+        let directory = match &self.directory {
+            Some(directory) => directory.join(DOCUMENTS_DIRECTORY),
+            None => return,
+        };
+
+        let _file_guard = self.file_lock.lock().unwrap();
+        let (index, changed_entries, removed_paths) = {
+            let mut state = self.state.lock().unwrap();
+            if !state.is_dirty {
+                return;
+            }
+
+            state.prune();
+            state.is_dirty = false;
+            state.last_saved = Some(Instant::now());
+            let index = match &state.index {
+                Some(index) => index.clone(),
+                None => return,
+            };
+
+            let changed_paths: Vec<_> = state.changed_paths.drain().collect();
+            let changed_entries: Vec<_> = changed_paths
+                .into_iter()
+                .filter_map(|path| {
+                    let entry = state.entries.get(&path)?.clone();
+                    Some((path, entry))
+                })
+                .collect();
+            let removed_paths: Vec<_> = state.removed_paths.drain().collect();
+            (index, changed_entries, removed_paths)
+        };
+
+        let mut failed_paths = Vec::new();
+        for (path, entry) in &changed_entries {
+            if let Err(why) = write(&directory, &entry_file_name(path), entry) {
+                warn!("Unable to write the document cache: {}", why);
+                failed_paths.push(path.clone());
+            }
+        }
+
+        for path in &removed_paths {
+            let _ = fs::remove_file(directory.join(entry_file_name(path)));
+        }
+
+        let mut is_dirty = !failed_paths.is_empty();
+        if let Err(why) = write(&directory, INDEX_FILE, &index) {
+            warn!("Unable to write the document cache: {}", why);
+            is_dirty = true;
+        }
+
+        if let Some(parent) = directory.parent() {
+            let _ = fs::remove_file(parent.join(LEGACY_DOCUMENTS_FILE));
+        }
+
+        if is_dirty {
+            let mut state = self.state.lock().unwrap();
+            state.changed_paths.extend(failed_paths);
+            state.is_dirty = true;
+        }
+    }
+
+    pub fn resolver(&self, kind: DistributionKind) -> Option<Resolver> {
+        let entry: ResolverEntry = read(self.directory.as_ref()?, RESOLVER_FILE)?;
+        if entry.kind == kind {
+            Some(entry.resolver)
+        } else {
+            None
+        }
+    }
+
+    pub fn store_resolver(&self, kind: DistributionKind, resolver: &Resolver) {
+        let directory = match &self.directory {
+            Some(directory) => directory,
+            None => return,
+        };
+
+        let entry = ResolverEntry {
+            kind,
+            resolver: resolver.clone(),
+        };
+        if let Err(why) = write(directory, RESOLVER_FILE, &entry) {
+            warn!("Unable to write the file database cache: {}", why);
+        }
+    }
+
+    fn load_index<'a>(&self, state: &'a mut CacheState) -> &'a mut HashMap<PathBuf, IndexEntry> {
+        let directory = self.directory.as_ref();
+        state.index.get_or_insert_with(|| {
+            directory
+                .and_then(|directory| read(&directory.join(DOCUMENTS_DIRECTORY), INDEX_FILE))
+                .unwrap_or_default()
+        })
+    }
+
+    /// Returns the document from memory or reads it from its file.
+    /// The document is removed from the index if its file cannot be read.
+    fn load_entry(&self, state: &mut CacheState, path: &Path) -> Option<CacheEntry> {
+        if let Some(entry) = state.entries.get(path) {
+            return Some(entry.clone());
+        }
+
+        let index_entry = self.load_index(state).get(path)?.clone();
+        let entry = self.directory.as_ref().and_then(|directory| {
+            read::<CacheEntry>(&directory.join(DOCUMENTS_DIRECTORY), &entry_file_name(path))
+        });
+
+        match entry {
+            Some(mut entry) if entry.hash == index_entry.hash => {
+                entry.modified = index_entry.modified;
+                state.entries.insert(path.to_owned(), entry.clone());
+                Some(entry)
+            }
+            _ => {
+                state.remove(path);
+                None
+            }
+        }
+    }
+}
+
+/// The hash is only compared against entries written by the same build,
+/// so a change of the hashing algorithm merely invalidates the cache.
+fn hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn entry_file_name(path: &Path) -> String {
+    format!("{:016x}.json", hash(&path.to_string_lossy()))
+}
+
+fn read<T: DeserializeOwned>(directory: &Path, name: &str) -> Option<T> {
+    let file = directory.join(name);
+    let text = fs::read_to_string(&file).ok()?;
+    match serde_json::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(why) => {
+            warn!(
+                "Discarding invalid cache file ({}): {}",
+                why,
+                file.display()
+            );
+            None
+        }
+    }
+}
+
+fn write<T: Serialize>(directory: &Path, name: &str, value: &T) -> std::io::Result<()> {
+    fs::create_dir_all(directory)?;
+    let text = serde_json::to_string(value)?;
+    let file = directory.join(name);
+    let temp_file = file.with_extension("tmp");
+    fs::write(&temp_file, text)?;
+    fs::rename(temp_file, file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use texlab_distro::Language;
+    use texlab_protocol::{Options, Uri};
+    use texlab_syntax::{SyntaxTree, SyntaxTreeInput};
+
+    fn snapshot() -> SyntaxTreeSnapshot {
+        SyntaxTreeSnapshot::Latex {
+            root: Default::default(),
+            comments: Vec::new(),
+            verbatim_environments: Vec::new(),
+        }
+    }
+
+    fn insert(cache: &DocumentCache, path: &Path, text: &str) {
+        fs::write(path, text).unwrap();
+        let modified = fs::metadata(path).unwrap().modified().unwrap();
+        cache.insert(path.to_owned(), modified, text.into(), snapshot());
+    }
+
+    fn expire(cache: &DocumentCache, path: &Path) {
+        let mut state = cache.state.lock().unwrap();
+        let entry = state.index.as_mut().unwrap().get_mut(path).unwrap();
+        entry.modified = SystemTime::UNIX_EPOCH;
+    }
+
+    #[test]
+    fn unchanged_file() {
+        let directory = tempdir().unwrap();
+        let file = directory.path().join("foo.tex");
+        let cache = DocumentCache::default();
+        insert(&cache, &file, "foo");
+        assert_eq!(cache.get(&file).unwrap().text, "foo");
+    }
+
+    #[test]
+    fn touched_file() {
+        let directory = tempdir().unwrap();
+        let file = directory.path().join("foo.tex");
+        let cache = DocumentCache::default();
+        insert(&cache, &file, "foo");
+        expire(&cache, &file);
+
+        let entry = cache.get(&file).unwrap();
+        assert_eq!(
+            entry.modified,
+            fs::metadata(&file).unwrap().modified().unwrap()
+        );
+    }
+
+    #[test]
+    fn changed_file() {
+        let directory = tempdir().unwrap();
+        let file = directory.path().join("foo.tex");
+        let cache = DocumentCache::default();
+        insert(&cache, &file, "foo");
+        expire(&cache, &file);
+        fs::write(&file, "bar").unwrap();
+
+        assert_eq!(cache.get(&file), None);
+        assert!(cache.entries_in(directory.path()).is_empty());
+    }
+
+    #[test]
+    fn deleted_file() {
+        let directory = tempdir().unwrap();
+        let file = directory.path().join("foo.tex");
+        let cache = DocumentCache::default();
+        insert(&cache, &file, "foo");
+        fs::remove_file(&file).unwrap();
+
+        assert!(cache.entries_in(directory.path()).is_empty());
+    }
+
+    #[test]
+    fn save_and_reload() {
+        let directory = tempdir().unwrap();
+        let file = directory.path().join("foo.tex");
+        let cache_directory = directory.path().join("cache");
+        let cache = DocumentCache::new(Some(cache_directory.clone()));
+        insert(&cache, &file, "foo");
+        cache.save();
+
+        let cache = DocumentCache::new(Some(cache_directory));
+        let entries = cache.entries_in(directory.path());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, file);
+        assert_eq!(entries[0].1.text, "foo");
+    }
+
+    #[test]
+    fn save_separate_files() {
+        let directory = tempdir().unwrap();
+        let cache_directory = directory.path().join("cache");
+        let cache = DocumentCache::new(Some(cache_directory.clone()));
+        insert(&cache, &directory.path().join("foo.tex"), "foo");
+        insert(&cache, &directory.path().join("bar.tex"), "bar");
+        cache.save();
+
+        let files = fs::read_dir(cache_directory.join(DOCUMENTS_DIRECTORY)).unwrap();
+        assert_eq!(files.count(), 3);
+    }
+
+    #[test]
+    fn prune_unused_entries() {
+        let directory = tempdir().unwrap();
+        let file = directory.path().join("foo.tex");
+        let cache_directory = directory.path().join("cache");
+        let cache = DocumentCache::new(Some(cache_directory.clone()));
+        insert(&cache, &file, "foo");
+        {
+            let mut state = cache.state.lock().unwrap();
+            let entry = state.index.as_mut().unwrap().get_mut(&file).unwrap();
+            entry.last_used = SystemTime::UNIX_EPOCH;
+        }
+        cache.save();
+
+        let files = fs::read_dir(cache_directory.join(DOCUMENTS_DIRECTORY)).unwrap();
+        assert_eq!(files.count(), 1);
+        let cache = DocumentCache::new(Some(cache_directory));
+        assert!(cache.entries_in(directory.path()).is_empty());
+    }
+
+    #[test]
+    fn snapshot_roundtrip() {
+        let uri = Uri::from_file_path("/foo/bar.tex").unwrap();
+        let text = "\\section{Foo}\n% TODO\n\\begin{verbatim}\\foo\\end{verbatim}";
+        let options = Options::default();
+        let resolver = Resolver::default();
+        let input = SyntaxTreeInput {
+            options: &options,
+            resolver: &resolver,
+            uri: &uri,
+            text,
+            language: Language::Latex,
+        };
+        let tree = SyntaxTree::parse(input);
+        let json = serde_json::to_string(&tree.snapshot(&options)).unwrap();
+        let snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(SyntaxTree::restore(input, snapshot), tree);
+    }
+
+    #[test]
+    fn resolver() {
+        let directory = tempdir().unwrap();
+        let cache = DocumentCache::new(Some(directory.path().to_owned()));
+        let mut files_by_name = HashMap::new();
+        files_by_name.insert("foo.sty".into(), PathBuf::from("/texmf/foo.sty"));
+        let resolver = Resolver::new(files_by_name);
+        cache.store_resolver(DistributionKind::Texlive, &resolver);
+
+        assert_eq!(cache.resolver(DistributionKind::Texlive), Some(resolver));
+        assert_eq!(cache.resolver(DistributionKind::Miktex), None);
+    }
+}
@@ -0,0 +1,47 @@
+use log::*;
+use std::path::{Path, PathBuf};
+use texlab_protocol::*;
+use tokio::fs;
+
+const AUXILIARY_EXTENSIONS: &[&str] = &[
+    "aux",
+    "bbl",
+    "bcf",
+    "blg",
+    "fdb_latexmk",
+    "fls",
+    "idx",
+    "ilg",
+    "ind",
+    "lof",
+    "log",
+    "lot",
+    "nav",
+    "out",
+    "run.xml",
+    "snm",
+    "synctex",
+    "synctex.gz",
+    "toc",
+    "vrb",
+];
+
+pub async fn clean(parent: &Path, options: &Options) -> Vec<PathBuf> {
+    let mut removed_files = Vec::new();
+    for extension in AUXILIARY_EXTENSIONS {
+        let path = match options.resolve_output_file(parent, extension) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        if !path.is_file() {
+            continue;
+        }
+
+        match fs::remove_file(&path).await {
+            Ok(()) => removed_files.push(path),
+            Err(why) => warn!("Unable to delete {}: {}", path.display(), why),
+        }
+    }
+    removed_files
+}
@@ -0,0 +1,129 @@
+use crate::diagnostics::{BibtexError, BibtexErrorCode};
+use futures_boxed::boxed;
+use std::collections::HashMap;
+use texlab_protocol::*;
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BibtexErrorCodeActionProvider;
+
+impl BibtexErrorCodeActionProvider {
+    fn insertion(code: BibtexErrorCode) -> Option<&'static str> {
+        match code {
+            BibtexErrorCode::MissingBeginBrace => Some("{"),
+            BibtexErrorCode::MissingEntryKey => None,
+            BibtexErrorCode::MissingComma => Some(","),
+            BibtexErrorCode::MissingEndBrace => Some("}"),
+            BibtexErrorCode::MissingAssign => Some(" = "),
+            BibtexErrorCode::MissingContent => Some("{}"),
+            BibtexErrorCode::MissingQuote => Some("\""),
+        }
+    }
+}
+
+impl FeatureProvider for BibtexErrorCodeActionProvider {
+    type Params = CodeActionParams;
+    type Output = Vec<CodeAction>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<CodeActionParams>,
+    ) -> Vec<CodeAction> {
+        let mut actions = Vec::new();
+        if let SyntaxTree::Bibtex(tree) = &request.document().tree {
+            for error in BibtexError::analyze(&tree)
+                .into_iter()
+                .filter(|error| request.params.range.contains(error.position))
+            {
+                if let Some(text) = Self::insertion(error.code) {
+                    let edit =
+                        TextEdit::new(Range::new(error.position, error.position), text.into());
+                    let mut changes = HashMap::new();
+                    changes.insert(request.document().uri.clone().into(), vec![edit]);
+                    actions.push(CodeAction {
+                        title: format!("Insert \"{}\"", text.trim()),
+                        kind: Some("quickfix".into()),
+                        diagnostics: Some(vec![error.into()]),
+                        edit: Some(WorkspaceEdit::new(changes)),
+                        ..CodeAction::default()
+                    });
+                }
+            }
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_comma() {
+        let actions = test_feature(
+            BibtexErrorCodeActionProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.bib", "@article{foo}")],
+                main_file: "foo.bib",
+                range: Range::new_simple(0, 0, 0, 13),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].title, "Insert \",\"");
+
+        let uri: Url = FeatureSpec::uri("foo.bib");
+        let changes = actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap();
+        assert_eq!(
+            changes[&uri],
+            vec![TextEdit::new(Range::new_simple(0, 12, 0, 12), ",".into())]
+        );
+    }
+
+    #[test]
+    fn missing_assign() {
+        let actions = test_feature(
+            BibtexErrorCodeActionProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.bib", "@article{foo, bar}")],
+                main_file: "foo.bib",
+                range: Range::new_simple(0, 14, 0, 17),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].title, "Insert \"=\"");
+    }
+
+    #[test]
+    fn outside_range() {
+        let actions = test_feature(
+            BibtexErrorCodeActionProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.bib",
+                    "@article{foo}\n@article{bar,}",
+                )],
+                main_file: "foo.bib",
+                range: Range::new_simple(1, 0, 1, 14),
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn latex() {
+        let actions = test_feature(
+            BibtexErrorCodeActionProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(actions.is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+mod bibtex_error;
+
+use self::bibtex_error::BibtexErrorCodeActionProvider;
+use futures_boxed::boxed;
+use texlab_protocol::{CodeAction, CodeActionParams};
+use texlab_workspace::*;
+
+pub struct CodeActionProvider {
+    provider: ConcatProvider<CodeActionParams, CodeAction>,
+}
+
+impl CodeActionProvider {
+    pub fn new() -> Self {
+        Self {
+            provider: ConcatProvider::new(vec![Box::new(BibtexErrorCodeActionProvider)]),
+        }
+    }
+}
+
+impl Default for CodeActionProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeatureProvider for CodeActionProvider {
+    type Params = CodeActionParams;
+    type Output = Vec<CodeAction>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<CodeActionParams>,
+    ) -> Vec<CodeAction> {
+        self.provider.execute(request).await
+    }
+}
@@ -0,0 +1,78 @@
+use super::reference_lens;
+use crate::reference::ReferenceProvider;
+use futures_boxed::boxed;
+use std::sync::Arc;
+use texlab_protocol::{CodeLens, CodeLensParams};
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+pub struct BibtexEntryCodeLensProvider {
+    reference_provider: Arc<ReferenceProvider>,
+}
+
+impl BibtexEntryCodeLensProvider {
+    pub fn new(reference_provider: Arc<ReferenceProvider>) -> Self {
+        Self { reference_provider }
+    }
+}
+
+impl FeatureProvider for BibtexEntryCodeLensProvider {
+    type Params = CodeLensParams;
+    type Output = Vec<CodeLens>;
+
+    #[boxed]
+    async fn execute<'a>(&'a self, request: &'a FeatureRequest<CodeLensParams>) -> Vec<CodeLens> {
+        let mut lenses = Vec::new();
+        if let SyntaxTree::Bibtex(tree) = &request.document().tree {
+            for key in tree
+                .entries()
+                .into_iter()
+                .filter(|entry| !entry.is_comment())
+                .filter_map(|entry| entry.key.as_ref())
+            {
+                lenses.push(reference_lens(&self.reference_provider, request, key.range()).await);
+            }
+        }
+        lenses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::Range;
+    use texlab_protocol::RangeExt;
+
+    #[test]
+    fn entry() {
+        let lenses = test_feature(
+            BibtexEntryCodeLensProvider::new(Arc::new(ReferenceProvider::new())),
+            FeatureSpec {
+                files: vec![
+                    FeatureSpec::file("foo.bib", "@article{foo,}\n@article{bar,}"),
+                    FeatureSpec::file("bar.tex", "\\addbibresource{foo.bib}\n\\cite{foo}"),
+                ],
+                main_file: "foo.bib",
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(lenses.len(), 2);
+        assert_eq!(lenses[0].range, Range::new_simple(0, 9, 0, 12));
+        assert_eq!(lenses[0].command.as_ref().unwrap().title, "1 reference");
+        assert_eq!(lenses[1].range, Range::new_simple(1, 9, 1, 12));
+        assert_eq!(lenses[1].command.as_ref().unwrap().title, "0 references");
+    }
+
+    #[test]
+    fn latex() {
+        let lenses = test_feature(
+            BibtexEntryCodeLensProvider::new(Arc::new(ReferenceProvider::new())),
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "\\label{foo}")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(lenses.is_empty());
+    }
+}
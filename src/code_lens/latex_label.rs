@@ -0,0 +1,86 @@
+use super::reference_lens;
+use crate::reference::ReferenceProvider;
+use futures_boxed::boxed;
+use std::sync::Arc;
+use texlab_protocol::{CodeLens, CodeLensParams};
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+pub struct LatexLabelCodeLensProvider {
+    reference_provider: Arc<ReferenceProvider>,
+}
+
+impl LatexLabelCodeLensProvider {
+    pub fn new(reference_provider: Arc<ReferenceProvider>) -> Self {
+        Self { reference_provider }
+    }
+}
+
+impl FeatureProvider for LatexLabelCodeLensProvider {
+    type Params = CodeLensParams;
+    type Output = Vec<CodeLens>;
+
+    #[boxed]
+    async fn execute<'a>(&'a self, request: &'a FeatureRequest<CodeLensParams>) -> Vec<CodeLens> {
+        let mut lenses = Vec::new();
+        if let SyntaxTree::Latex(tree) = &request.document().tree {
+            for label in tree
+                .structure
+                .labels
+                .iter()
+                .filter(|label| label.kind == LatexLabelKind::Definition)
+                .flat_map(LatexLabel::names)
+            {
+                lenses.push(reference_lens(&self.reference_provider, request, label.range()).await);
+            }
+        }
+        lenses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_lens::SHOW_REFERENCES_COMMAND;
+    use texlab_protocol::Range;
+    use texlab_protocol::RangeExt;
+
+    #[test]
+    fn label() {
+        let lenses = test_feature(
+            LatexLabelCodeLensProvider::new(Arc::new(ReferenceProvider::new())),
+            FeatureSpec {
+                files: vec![
+                    FeatureSpec::file("foo.tex", "\\label{foo}\n\\label{bar}\n\\ref{foo}"),
+                    FeatureSpec::file("bar.tex", "\\input{foo.tex}\n\\ref{foo}"),
+                ],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(lenses.len(), 2);
+        assert_eq!(lenses[0].range, Range::new_simple(0, 7, 0, 10));
+        assert_eq!(lenses[0].command.as_ref().unwrap().title, "2 references");
+        assert_eq!(
+            lenses[0].command.as_ref().unwrap().command,
+            SHOW_REFERENCES_COMMAND
+        );
+        let arguments = lenses[0].command.as_ref().unwrap().arguments.as_ref();
+        assert_eq!(arguments.unwrap()[2].as_array().unwrap().len(), 2);
+        assert_eq!(lenses[1].range, Range::new_simple(1, 7, 1, 10));
+        assert_eq!(lenses[1].command.as_ref().unwrap().title, "0 references");
+    }
+
+    #[test]
+    fn bibtex() {
+        let lenses = test_feature(
+            LatexLabelCodeLensProvider::new(Arc::new(ReferenceProvider::new())),
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.bib", "@article{foo,}")],
+                main_file: "foo.bib",
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(lenses.is_empty());
+    }
+}
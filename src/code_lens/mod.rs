@@ -0,0 +1,84 @@
+mod bibtex_entry;
+mod latex_label;
+
+use self::bibtex_entry::BibtexEntryCodeLensProvider;
+use self::latex_label::LatexLabelCodeLensProvider;
+use crate::reference::ReferenceProvider;
+use futures_boxed::boxed;
+use serde_json::json;
+use std::sync::Arc;
+use texlab_protocol::*;
+use texlab_workspace::*;
+
+/// Opens the reference list in the client.
+/// The command takes the URI, the position and the locations as arguments.
+const SHOW_REFERENCES_COMMAND: &str = "editor.action.showReferences";
+
+pub struct CodeLensProvider {
+    provider: ConcatProvider<CodeLensParams, CodeLens>,
+}
+
+impl CodeLensProvider {
+    pub fn new(reference_provider: Arc<ReferenceProvider>) -> Self {
+        Self {
+            provider: ConcatProvider::new(vec![
+                Box::new(BibtexEntryCodeLensProvider::new(Arc::clone(
+                    &reference_provider,
+                ))),
+                Box::new(LatexLabelCodeLensProvider::new(reference_provider)),
+            ]),
+        }
+    }
+}
+
+impl FeatureProvider for CodeLensProvider {
+    type Params = CodeLensParams;
+    type Output = Vec<CodeLens>;
+
+    #[boxed]
+    async fn execute<'a>(&'a self, request: &'a FeatureRequest<CodeLensParams>) -> Vec<CodeLens> {
+        self.provider.execute(request).await
+    }
+}
+
+async fn reference_lens(
+    reference_provider: &ReferenceProvider,
+    request: &FeatureRequest<CodeLensParams>,
+    range: Range,
+) -> CodeLens {
+    let text_document = request.params.text_document.clone();
+    let reference_request = FeatureRequest {
+        params: ReferenceParams {
+            text_document_position: TextDocumentPositionParams::new(text_document, range.start),
+            context: ReferenceContext {
+                include_declaration: false,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        },
+        view: request.view.clone(),
+        client_capabilities: Arc::clone(&request.client_capabilities),
+        distribution: Arc::clone(&request.distribution),
+        options: request.options.clone(),
+    };
+    let locations = reference_provider.execute(&reference_request).await;
+
+    let title = if locations.len() == 1 {
+        "1 reference".to_owned()
+    } else {
+        format!("{} references", locations.len())
+    };
+    let arguments = vec![
+        json!(request.params.text_document.uri),
+        json!(range.start),
+        json!(locations),
+    ];
+    CodeLens {
+        range,
+        command: Some(Command::new(
+            title,
+            SHOW_REFERENCES_COMMAND.to_owned(),
+            Some(arguments),
+        )),
+        data: None,
+    }
+}
@@ -2,12 +2,16 @@ use futures::lock::Mutex;
 use futures_boxed::boxed;
 use log::*;
 use serde::de::DeserializeOwned;
+use std::collections::HashMap;
 use std::sync::Arc;
 use texlab_protocol::*;
 
 pub trait ConfigStrategy: Send + Sync {
+    /// Returns the options of a document or workspace folder.
+    /// The cache is used unless `fetch` is set.
+    /// Without a scope, the global options are returned.
     #[boxed]
-    async fn get(&self, fetch: bool) -> Options;
+    async fn get(&self, scope: Option<Uri>, fetch: bool) -> Options;
 
     #[boxed()]
     async fn set(&self, settings: serde_json::Value);
@@ -29,25 +33,25 @@ impl dyn ConfigStrategy {
 #[derive(Debug)]
 struct PullConfigStrategy<C> {
     client: Arc<C>,
-    options: Mutex<Options>,
+    options_by_scope: Mutex<HashMap<Option<Uri>, Options>>,
 }
 
 impl<C: LspClient> PullConfigStrategy<C> {
     pub fn new(client: Arc<C>) -> Self {
         Self {
             client,
-            options: Mutex::default(),
+            options_by_scope: Mutex::default(),
         }
     }
 
-    async fn configuration<T>(&self, section: &'static str) -> T
+    async fn configuration<T>(&self, section: &'static str, scope: Option<&Uri>) -> T
     where
         T: DeserializeOwned + Default,
     {
         let params = ConfigurationParams {
             items: vec![ConfigurationItem {
                 section: Some(section.into()),
-                scope_uri: None,
+                scope_uri: scope.map(|uri| uri.clone().into()),
             }],
         };
 
@@ -72,20 +76,31 @@ impl<C: LspClient> PullConfigStrategy<C> {
 
 impl<C: LspClient + Send + Sync> ConfigStrategy for PullConfigStrategy<C> {
     #[boxed]
-    async fn get(&self, fetch: bool) -> Options {
-        if fetch {
-            let options = Options {
-                latex: Some(self.configuration("latex").await),
-                bibtex: Some(self.configuration("bibtex").await),
-            };
-            let mut options_guard = self.options.lock().await;
-            *options_guard = options;
+    async fn get(&self, scope: Option<Uri>, fetch: bool) -> Options {
+        if !fetch {
+            if let Some(options) = self.options_by_scope.lock().await.get(&scope) {
+                return options.clone();
+            }
         }
-        self.options.lock().await.clone()
+
+        let options = Options {
+            latex: Some(self.configuration("latex", scope.as_ref()).await),
+            bibtex: Some(self.configuration("bibtex", scope.as_ref()).await),
+        };
+        let mut options_by_scope = self.options_by_scope.lock().await;
+        options_by_scope.insert(scope, options.clone());
+        options
     }
 
+    /// Fetches the options of the known scopes again.
+    /// The previous options are kept until the new ones have arrived.
     #[boxed]
-    async fn set(&self, _settings: serde_json::Value) {}
+    async fn set(&self, _settings: serde_json::Value) {
+        let scopes: Vec<_> = self.options_by_scope.lock().await.keys().cloned().collect();
+        for scope in scopes {
+            self.get(scope, true).await;
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -101,7 +116,7 @@ impl PushConfigStrategy {
 
 impl ConfigStrategy for PushConfigStrategy {
     #[boxed]
-    async fn get(&self, _fetch: bool) -> Options {
+    async fn get(&self, _scope: Option<Uri>, _fetch: bool) -> Options {
         let options = self.options.lock().await;
         options.clone()
     }
@@ -0,0 +1,137 @@
+use log::warn;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use texlab_protocol::Options;
+
+pub const CONFIG_FILE_NAMES: &[&str] = &[".texlabrc", "texlab.toml"];
+
+#[derive(Debug, Default)]
+pub struct ConfigFileManager {
+    options_by_path: Mutex<HashMap<PathBuf, Option<Options>>>,
+}
+
+impl ConfigFileManager {
+    /// Searches for a project configuration file from `path` upwards, stopping at `ceiling`.
+    pub fn find(&self, path: &Path, ceiling: Option<&Path>) -> Option<Options> {
+        let mut options_by_path = self.options_by_path.lock().unwrap();
+        options_by_path
+            .entry(path.to_owned())
+            .or_insert_with(|| Self::load(path, ceiling))
+            .clone()
+    }
+
+    pub fn clear(&self) {
+        self.options_by_path.lock().unwrap().clear();
+    }
+
+    fn load(path: &Path, ceiling: Option<&Path>) -> Option<Options> {
+        for directory in path.ancestors() {
+            for name in CONFIG_FILE_NAMES {
+                let file = directory.join(name);
+                if !file.is_file() {
+                    continue;
+                }
+
+                let options = fs::read_to_string(&file)
+                    .map_err(|why| why.to_string())
+                    .and_then(|text| toml::from_str(&text).map_err(|why| why.to_string()));
+
+                match options {
+                    Ok(options) => return Some(options),
+                    Err(why) => {
+                        warn!("Invalid configuration file ({}): {}", why, file.display());
+                        return None;
+                    }
+                }
+            }
+
+            if ceiling.map_or(true, |ceiling| directory == ceiling) {
+                break;
+            }
+        }
+        None
+    }
+}
+
+pub fn is_config_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map_or(false, |name| CONFIG_FILE_NAMES.contains(&name))
+}
+
+/// Merges the options so that every setting of `overlay` takes precedence over `base`.
+pub fn merge(base: Options, overlay: Options) -> Options {
+    let mut value = serde_json::to_value(base).unwrap();
+    merge_value(&mut value, serde_json::to_value(overlay).unwrap());
+    serde_json::from_value(value).unwrap_or_default()
+}
+
+fn merge_value(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (_, Value::Null) => (),
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge_value(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::*;
+
+    #[test]
+    fn merge_overlay() {
+        let base = Options {
+            latex: Some(LatexOptions {
+                build: Some(LatexBuildOptions {
+                    executable: Some("tectonic".into()),
+                    on_save: Some(true),
+                    ..LatexBuildOptions::default()
+                }),
+                ..LatexOptions::default()
+            }),
+            bibtex: None,
+        };
+        let overlay = Options {
+            latex: Some(LatexOptions {
+                build: Some(LatexBuildOptions {
+                    executable: Some("latexmk".into()),
+                    ..LatexBuildOptions::default()
+                }),
+                ..LatexOptions::default()
+            }),
+            bibtex: Some(BibtexOptions::default()),
+        };
+
+        let build = merge(base, overlay).latex.unwrap().build.unwrap();
+        assert_eq!(build.executable, Some("latexmk".into()));
+        assert_eq!(build.on_save, Some(true));
+    }
+
+    #[test]
+    fn merge_empty_overlay() {
+        let base = Options {
+            latex: None,
+            bibtex: Some(BibtexOptions {
+                formatting: Some(BibtexFormattingOptions {
+                    line_length: Some(0),
+                }),
+            }),
+        };
+        assert_eq!(merge(base.clone(), Options::default()), base);
+    }
+
+    #[test]
+    fn is_config_file_name() {
+        assert!(is_config_file(Path::new("/foo/.texlabrc")));
+        assert!(is_config_file(Path::new("/foo/texlab.toml")));
+        assert!(!is_config_file(Path::new("/foo/bar.toml")));
+    }
+}
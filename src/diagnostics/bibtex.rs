@@ -29,8 +29,8 @@ impl BibtexErrorCode {
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct BibtexError {
-    code: BibtexErrorCode,
-    position: Position,
+    pub code: BibtexErrorCode,
+    pub position: Position,
 }
 
 impl BibtexError {
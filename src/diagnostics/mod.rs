@@ -1,13 +1,15 @@
 mod bibtex;
 mod build;
 mod latex;
+mod todo;
 
-pub use self::bibtex::BibtexErrorCode;
+pub use self::bibtex::{BibtexError, BibtexErrorCode};
 
 use self::bibtex::BibtexDiagnosticsProvider;
 use self::build::BuildDiagnosticsProvider;
 use self::latex::LatexDiagnosticsProvider;
-use texlab_protocol::Diagnostic;
+use self::todo::TodoDiagnosticsProvider;
+use texlab_protocol::{Diagnostic, Options};
 use texlab_workspace::Document;
 
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
@@ -15,14 +17,16 @@ pub struct DiagnosticsManager {
     pub build: BuildDiagnosticsProvider,
     pub latex: LatexDiagnosticsProvider,
     pub bibtex: BibtexDiagnosticsProvider,
+    pub todo: TodoDiagnosticsProvider,
 }
 
 impl DiagnosticsManager {
-    pub fn get(&self, document: &Document) -> Vec<Diagnostic> {
+    pub fn get(&self, document: &Document, options: &Options) -> Vec<Diagnostic> {
         let mut diagnostics = Vec::new();
         diagnostics.append(&mut self.build.get(document));
         diagnostics.append(&mut self.latex.get(document));
         diagnostics.append(&mut self.bibtex.get(document));
+        diagnostics.append(&mut self.todo.get(document, options));
         diagnostics
     }
 }
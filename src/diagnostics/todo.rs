@@ -0,0 +1,27 @@
+use crate::todo::find_todos;
+use texlab_protocol::{Diagnostic, DiagnosticSeverity, Options};
+use texlab_workspace::Document;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct TodoDiagnosticsProvider;
+
+impl TodoDiagnosticsProvider {
+    pub fn get(&self, document: &Document, options: &Options) -> Vec<Diagnostic> {
+        find_todos(document, options)
+            .into_iter()
+            .map(|todo| Diagnostic {
+                source: Some("texlab".into()),
+                message: if todo.message.is_empty() {
+                    todo.tag
+                } else {
+                    format!("{}: {}", todo.tag, todo.message)
+                },
+                severity: Some(DiagnosticSeverity::Information),
+                range: todo.location.range,
+                code: None,
+                related_information: None,
+                tags: None,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use texlab_workspace::Workspace;
+
+#[derive(Debug, Default)]
+pub struct FolderManager {
+    folders: Mutex<Vec<PathBuf>>,
+    restricted: Mutex<HashMap<Option<PathBuf>, (Arc<Workspace>, Arc<Workspace>)>>,
+}
+
+impl FolderManager {
+    pub fn get(&self) -> Vec<PathBuf> {
+        self.folders.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, folders: Vec<PathBuf>) {
+        *self.folders.lock().unwrap() = folders;
+        self.restricted.lock().unwrap().clear();
+    }
+
+    pub fn add(&self, folder: PathBuf) {
+        let mut folders = self.folders.lock().unwrap();
+        if !folders.contains(&folder) {
+            folders.push(folder);
+            self.restricted.lock().unwrap().clear();
+        }
+    }
+
+    pub fn remove(&self, folder: &Path) {
+        let mut folders = self.folders.lock().unwrap();
+        folders.retain(|other| other != folder);
+        self.restricted.lock().unwrap().clear();
+    }
+
+    /// Returns the innermost workspace folder that contains the given path.
+    pub fn find(&self, path: &Path) -> Option<PathBuf> {
+        let folders = self.folders.lock().unwrap();
+        Self::find_in(&folders, path).cloned()
+    }
+
+    /// Restricts the workspace to the documents of the given folder.
+    /// Documents outside of every workspace folder are shared between all folders.
+    ///
+    /// The result is reused until the workspace or the folders change.
+    pub fn restrict(&self, workspace: &Arc<Workspace>, folder: Option<&Path>) -> Arc<Workspace> {
+        let folders = self.folders.lock().unwrap();
+        if folders.is_empty() || (folders.len() == 1 && folder.is_some()) {
+            return Arc::clone(workspace);
+        }
+
+        let mut restricted = self.restricted.lock().unwrap();
+        let key = folder.map(ToOwned::to_owned);
+        if let Some((source, result)) = restricted.get(&key) {
+            if Arc::ptr_eq(source, workspace) {
+                return Arc::clone(result);
+            }
+        }
+
+        let result: Arc<Workspace> = Arc::new(
+            workspace
+                .documents()
+                .filter(|document| {
+                    let owner = document
+                        .uri
+                        .to_file_path()
+                        .ok()
+                        .and_then(|path| Self::find_in(&folders, &path));
+
+                    owner.is_none() || owner.map(PathBuf::as_path) == folder
+                })
+                .cloned()
+                .collect(),
+        );
+        restricted.insert(key, (Arc::clone(workspace), Arc::clone(&result)));
+        result
+    }
+
+    fn find_in<'a>(folders: &'a [PathBuf], path: &Path) -> Option<&'a PathBuf> {
+        folders
+            .iter()
+            .filter(|folder| path.starts_with(folder))
+            .max_by_key(|folder| folder.components().count())
+    }
+}
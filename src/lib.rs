@@ -2,14 +2,27 @@
 
 pub mod action;
 pub mod build;
+pub mod cache;
+pub mod clean;
+pub mod code_action;
+pub mod code_lens;
 pub mod config;
+pub mod config_file;
 pub mod definition;
 pub mod diagnostics;
+pub mod folder;
 pub mod folding;
 pub mod forward_search;
 pub mod highlight;
 pub mod link;
+pub mod on_type_formatting;
+pub mod progress;
 pub mod reference;
 pub mod rename;
+pub mod selection_range;
+pub mod semantic_tokens;
 pub mod server;
+pub mod signature_help;
+pub mod todo;
+pub mod watcher;
 pub mod workspace_manager;
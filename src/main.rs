@@ -5,6 +5,7 @@ use jsonrpc::MessageHandler;
 use std::error::Error;
 use std::sync::Arc;
 use stderrlog::{ColorChoice, Timestamp};
+use texlab::cache::DocumentCache;
 use texlab::server::LatexLspServer;
 use texlab_distro::Distribution;
 use texlab_protocol::{LatexLspClient, LspCodec};
@@ -53,12 +54,9 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let server = Arc::new(LatexLspServer::new(
         Arc::clone(&client),
         Arc::new(Distribution::detect().await),
+        Arc::new(DocumentCache::new(DocumentCache::default_directory())),
     ));
-    let mut handler = MessageHandler {
-        server,
-        client,
-        output: stdout_tx,
-    };
+    let mut handler = MessageHandler::new(server, client, stdout_tx);
 
     tokio::spawn(async move {
         let mut stdout = FramedWrite::new(tokio::io::stdout(), LspCodec);
@@ -0,0 +1,163 @@
+use futures_boxed::boxed;
+use std::sync::Arc;
+use texlab_protocol::{DocumentOnTypeFormattingParams, Range, TextEdit};
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LatexEnvironmentOnTypeFormattingProvider;
+
+impl FeatureProvider for LatexEnvironmentOnTypeFormattingProvider {
+    type Params = DocumentOnTypeFormattingParams;
+    type Output = Vec<TextEdit>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<DocumentOnTypeFormattingParams>,
+    ) -> Vec<TextEdit> {
+        let mut edits = Vec::new();
+        if request.params.ch != "}" {
+            return edits;
+        }
+
+        let position = request.params.text_document_position.position;
+        if let SyntaxTree::Latex(tree) = &request.document().tree {
+            let begin = tree.commands.iter().find(|command| {
+                command.name.text() == "\\begin"
+                    && command
+                        .args
+                        .get(0)
+                        .and_then(|arg| arg.right.as_ref())
+                        .map_or(false, |right| right.end() == position)
+            });
+
+            let begin = match begin {
+                Some(begin) => begin,
+                None => return edits,
+            };
+
+            let name = match begin.extract_word(0) {
+                Some(name) => name.text(),
+                None => return edits,
+            };
+
+            let is_closed = tree.env.environments.iter().any(|environment| {
+                Arc::ptr_eq(&environment.left.command, begin)
+                    && environment.right.name().map(LatexToken::text) == Some(name)
+            });
+            if is_closed {
+                return edits;
+            }
+
+            let line = request
+                .document()
+                .text
+                .lines()
+                .nth(begin.start().line as usize)
+                .unwrap_or_default();
+            let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+
+            let mut text = String::new();
+            if LANGUAGE_DATA
+                .enum_environments
+                .iter()
+                .any(|env| env == name)
+            {
+                let options = &request.params.options;
+                let inner_indent = if options.insert_spaces {
+                    " ".repeat(options.tab_size as usize)
+                } else {
+                    "\t".to_owned()
+                };
+                text.push_str(&format!("\n{}{}\\item", indent, inner_indent));
+            }
+            text.push_str(&format!("\n{}\\end{{{}}}", indent, name));
+            edits.push(TextEdit::new(Range::new(position, position), text));
+        }
+        edits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::{Position, RangeExt};
+
+    #[test]
+    fn environment() {
+        let edits = test_feature(
+            LatexEnvironmentOnTypeFormattingProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "  \\begin{align}")],
+                main_file: "foo.tex",
+                position: Position::new(0, 15),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            edits,
+            vec![TextEdit::new(
+                Range::new_simple(0, 15, 0, 15),
+                "\n  \\end{align}".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn enumeration() {
+        let edits = test_feature(
+            LatexEnvironmentOnTypeFormattingProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "\\begin{itemize}")],
+                main_file: "foo.tex",
+                position: Position::new(0, 15),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            edits,
+            vec![TextEdit::new(
+                Range::new_simple(0, 15, 0, 15),
+                "\n    \\item\n\\end{itemize}".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn already_closed() {
+        let edits = test_feature(
+            LatexEnvironmentOnTypeFormattingProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "\\begin{align}\n\\end{align}")],
+                main_file: "foo.tex",
+                position: Position::new(0, 13),
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(edits.is_empty());
+    }
+
+    #[test]
+    fn mismatched_end() {
+        let edits = test_feature(
+            LatexEnvironmentOnTypeFormattingProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.tex",
+                    "\\begin{document}\n\\begin{align}\n\\end{document}",
+                )],
+                main_file: "foo.tex",
+                position: Position::new(1, 13),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            edits,
+            vec![TextEdit::new(
+                Range::new_simple(1, 13, 1, 13),
+                "\n\\end{align}".into()
+            )]
+        );
+    }
+}
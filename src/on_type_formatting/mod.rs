@@ -0,0 +1,37 @@
+mod latex_environment;
+
+use self::latex_environment::LatexEnvironmentOnTypeFormattingProvider;
+use futures_boxed::boxed;
+use texlab_protocol::{DocumentOnTypeFormattingParams, TextEdit};
+use texlab_workspace::*;
+
+pub struct OnTypeFormattingProvider {
+    provider: ConcatProvider<DocumentOnTypeFormattingParams, TextEdit>,
+}
+
+impl OnTypeFormattingProvider {
+    pub fn new() -> Self {
+        Self {
+            provider: ConcatProvider::new(vec![Box::new(LatexEnvironmentOnTypeFormattingProvider)]),
+        }
+    }
+}
+
+impl Default for OnTypeFormattingProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeatureProvider for OnTypeFormattingProvider {
+    type Params = DocumentOnTypeFormattingParams;
+    type Output = Vec<TextEdit>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<DocumentOnTypeFormattingParams>,
+    ) -> Vec<TextEdit> {
+        self.provider.execute(request).await
+    }
+}
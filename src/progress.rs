@@ -0,0 +1,40 @@
+use log::warn;
+use texlab_protocol::*;
+use uuid::Uuid;
+
+pub fn token(kind: &str) -> ProgressToken {
+    ProgressToken::String(format!("texlab-{}-{}", kind, Uuid::new_v4()))
+}
+
+/// Creates the progress and reports its beginning.
+/// Returns `false` if the client refused to create the progress.
+pub async fn begin<C: LspClient>(
+    client: &C,
+    token: ProgressToken,
+    begin: WorkDoneProgressBegin,
+) -> bool {
+    let params = WorkDoneProgressCreateParams {
+        token: token.clone(),
+    };
+    if let Err(why) = client.work_done_progress_create(params).await {
+        warn!("Unable to create progress: {}", why.message);
+        return false;
+    }
+
+    let params = ProgressParams {
+        token,
+        value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(begin)),
+    };
+    client.progress(params).await;
+    true
+}
+
+pub async fn end<C: LspClient>(client: &C, token: ProgressToken) {
+    let params = ProgressParams {
+        token,
+        value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(WorkDoneProgressEnd {
+            message: None,
+        })),
+    };
+    client.progress(params).await;
+}
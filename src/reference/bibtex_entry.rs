@@ -15,7 +15,12 @@ impl FeatureProvider for BibtexEntryReferenceProvider {
     async fn execute<'a>(&'a self, request: &'a FeatureRequest<ReferenceParams>) -> Vec<Location> {
         let mut references = Vec::new();
         if let Some(key) = Self::find_key(request) {
-            for document in request.related_documents() {
+            let workspace = request.workspace();
+            let citation_uris = workspace.citation_documents(key);
+            let entry_uris = workspace.entry_documents(key);
+            for document in request.related_documents().iter().filter(|document| {
+                citation_uris.contains(&document.uri) || entry_uris.contains(&document.uri)
+            }) {
                 match &document.tree {
                     SyntaxTree::Latex(tree) => tree
                         .citations
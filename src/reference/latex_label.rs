@@ -15,7 +15,12 @@ impl FeatureProvider for LatexLabelReferenceProvider {
     async fn execute<'a>(&'a self, request: &'a FeatureRequest<ReferenceParams>) -> Vec<Location> {
         let mut references = Vec::new();
         if let Some(definition) = Self::find_name(request) {
-            for document in request.related_documents() {
+            let uris = request.workspace().label_documents(definition);
+            for document in request
+                .related_documents()
+                .iter()
+                .filter(|document| uris.contains(&document.uri))
+            {
                 if let SyntaxTree::Latex(tree) = &document.tree {
                     tree.structure
                         .labels
@@ -37,9 +37,17 @@ impl FeatureProvider for BibtexEntryRenameProvider {
             &request.document().tree,
             request.params.text_document_position.position,
         )?;
+        let workspace = request.workspace();
+        let citation_uris = workspace.citation_documents(&key_name.text);
+        let entry_uris = workspace.entry_documents(&key_name.text);
         let mut changes = HashMap::new();
         for document in request.related_documents() {
             let mut edits = Vec::new();
+            if !citation_uris.contains(&document.uri) && !entry_uris.contains(&document.uri) {
+                changes.insert(document.uri.clone().into(), edits);
+                continue;
+            }
+
             match &document.tree {
                 SyntaxTree::Latex(tree) => {
                     tree.citations
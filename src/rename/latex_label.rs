@@ -37,17 +37,20 @@ impl FeatureProvider for LatexLabelRenameProvider {
             &request.document().tree,
             request.params.text_document_position.position,
         )?;
+        let uris = request.workspace().label_documents(&name.text);
         let mut changes = HashMap::new();
         for document in request.related_documents() {
             if let SyntaxTree::Latex(tree) = &document.tree {
-                let edits = tree
-                    .structure
-                    .labels
-                    .iter()
-                    .flat_map(LatexLabel::names)
-                    .filter(|label| label.text() == name.text)
-                    .map(|label| TextEdit::new(label.range(), request.params.new_name.clone()))
-                    .collect();
+                let mut edits = Vec::new();
+                if uris.contains(&document.uri) {
+                    tree.structure
+                        .labels
+                        .iter()
+                        .flat_map(LatexLabel::names)
+                        .filter(|label| label.text() == name.text)
+                        .map(|label| TextEdit::new(label.range(), request.params.new_name.clone()))
+                        .for_each(|edit| edits.push(edit));
+                }
                 changes.insert(document.uri.clone().into(), edits);
             }
         }
@@ -0,0 +1,76 @@
+use super::build_selection_range;
+use futures_boxed::boxed;
+use texlab_protocol::{SelectionRange, SelectionRangeParams};
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BibtexSelectionRangeProvider;
+
+impl FeatureProvider for BibtexSelectionRangeProvider {
+    type Params = SelectionRangeParams;
+    type Output = Vec<SelectionRange>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<SelectionRangeParams>,
+    ) -> Vec<SelectionRange> {
+        let mut selections = Vec::new();
+        if let SyntaxTree::Bibtex(tree) = &request.document().tree {
+            for position in &request.params.positions {
+                let ranges = tree
+                    .find(*position)
+                    .into_iter()
+                    .map(|node| node.range())
+                    .collect();
+                selections.push(build_selection_range(*position, ranges));
+            }
+        }
+        selections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::{Position, Range, RangeExt};
+
+    #[test]
+    fn field() {
+        let selections = test_feature(
+            BibtexSelectionRangeProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.bib",
+                    "@article{foo,\ntitle = {foo bar}}",
+                )],
+                main_file: "foo.bib",
+                position: Position::new(1, 14),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(selections.len(), 1);
+        let word = &selections[0];
+        assert_eq!(word.range, Range::new_simple(1, 13, 1, 16));
+        let content = word.parent.as_ref().unwrap();
+        assert_eq!(content.range, Range::new_simple(1, 8, 1, 17));
+        let field = content.parent.as_ref().unwrap();
+        assert_eq!(field.range, Range::new_simple(1, 0, 1, 17));
+        let entry = field.parent.as_ref().unwrap();
+        assert_eq!(entry.range, Range::new_simple(0, 0, 1, 18));
+    }
+
+    #[test]
+    fn latex() {
+        let selections = test_feature(
+            BibtexSelectionRangeProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "\\foo")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(selections.is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+use super::build_selection_range;
+use futures_boxed::boxed;
+use texlab_protocol::{Range, RangeExt, SelectionRange, SelectionRangeParams};
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LatexSelectionRangeProvider;
+
+impl FeatureProvider for LatexSelectionRangeProvider {
+    type Params = SelectionRangeParams;
+    type Output = Vec<SelectionRange>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<SelectionRangeParams>,
+    ) -> Vec<SelectionRange> {
+        let mut selections = Vec::new();
+        if let SyntaxTree::Latex(tree) = &request.document().tree {
+            for position in &request.params.positions {
+                let position = *position;
+                let mut ranges = Vec::new();
+                for node in tree.find(position) {
+                    ranges.push(node.range());
+                    if let LatexNode::Text(text) = node {
+                        ranges.extend(
+                            text.words
+                                .iter()
+                                .map(|word| word.range())
+                                .filter(|range| range.contains(position)),
+                        );
+                    }
+                }
+
+                ranges.extend(
+                    tree.env
+                        .environments
+                        .iter()
+                        .map(|environment| environment.range())
+                        .filter(|range| range.contains(position)),
+                );
+
+                let sections = &tree.structure.sections;
+                for (i, current) in sections.iter().enumerate() {
+                    let end = sections
+                        .iter()
+                        .skip(i + 1)
+                        .find(|sec| current.level >= sec.level)
+                        .map(|next| next.start())
+                        .unwrap_or_else(|| tree.root.end());
+                    let range = Range::new(current.start(), end);
+                    if range.contains(position) {
+                        ranges.push(range);
+                    }
+                }
+
+                selections.push(build_selection_range(position, ranges));
+            }
+        }
+        selections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::Position;
+
+    fn ranges(selection: &SelectionRange) -> Vec<Range> {
+        let mut ranges = vec![selection.range];
+        let mut current = selection;
+        while let Some(parent) = &current.parent {
+            ranges.push(parent.range);
+            current = parent;
+        }
+        ranges
+    }
+
+    #[test]
+    fn environment() {
+        let selections = test_feature(
+            LatexSelectionRangeProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.tex",
+                    "\\section{Foo}\n\\begin{document}\n\\textbf{foo bar}\n\\end{document}",
+                )],
+                main_file: "foo.tex",
+                position: Position::new(2, 13),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(selections.len(), 1);
+        assert_eq!(
+            ranges(&selections[0]),
+            vec![
+                Range::new_simple(2, 12, 2, 15),
+                Range::new_simple(2, 8, 2, 15),
+                Range::new_simple(2, 7, 2, 16),
+                Range::new_simple(2, 0, 2, 16),
+                Range::new_simple(1, 0, 3, 14),
+                Range::new_simple(0, 0, 3, 14),
+            ]
+        );
+    }
+
+    #[test]
+    fn environment_crossing_section() {
+        let selections = test_feature(
+            LatexSelectionRangeProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.tex",
+                    "\\section{Foo}\n\\begin{foo}\nfoo\n\\section{Bar}\nbar\n\\end{foo}",
+                )],
+                main_file: "foo.tex",
+                position: Position::new(2, 1),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(selections.len(), 1);
+        assert_eq!(
+            ranges(&selections[0]),
+            vec![
+                Range::new_simple(2, 0, 2, 3),
+                Range::new_simple(1, 0, 5, 9),
+                Range::new_simple(0, 0, 5, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn bibtex() {
+        let selections = test_feature(
+            LatexSelectionRangeProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.bib", "@article{foo,}")],
+                main_file: "foo.bib",
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(selections.is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+mod bibtex;
+mod latex;
+
+use self::bibtex::BibtexSelectionRangeProvider;
+use self::latex::LatexSelectionRangeProvider;
+use futures_boxed::boxed;
+use std::cmp::Reverse;
+use texlab_protocol::{Position, Range, RangeExt, SelectionRange, SelectionRangeParams};
+use texlab_workspace::*;
+
+pub struct SelectionRangeProvider {
+    provider: ConcatProvider<SelectionRangeParams, SelectionRange>,
+}
+
+impl SelectionRangeProvider {
+    pub fn new() -> Self {
+        Self {
+            provider: ConcatProvider::new(vec![
+                Box::new(BibtexSelectionRangeProvider),
+                Box::new(LatexSelectionRangeProvider),
+            ]),
+        }
+    }
+}
+
+impl Default for SelectionRangeProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeatureProvider for SelectionRangeProvider {
+    type Params = SelectionRangeParams;
+    type Output = Vec<SelectionRange>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<SelectionRangeParams>,
+    ) -> Vec<SelectionRange> {
+        self.provider.execute(request).await
+    }
+}
+
+/// Nests the ranges from the innermost to the outermost one.
+/// Ranges that overlap without containing the inner ones are dropped,
+/// e.g. an environment that crosses a section boundary.
+fn build_selection_range(position: Position, mut ranges: Vec<Range>) -> SelectionRange {
+    ranges.sort_by_key(|range| (Reverse(range.start), range.end));
+    ranges.dedup();
+
+    let mut nested_ranges: Vec<Range> = Vec::new();
+    for range in ranges {
+        let is_nested = nested_ranges.last().map_or(true, |inner| {
+            range.contains(inner.start) && range.contains(inner.end)
+        });
+
+        if is_nested {
+            nested_ranges.push(range);
+        }
+    }
+
+    nested_ranges
+        .into_iter()
+        .rev()
+        .fold(None, |parent, range| {
+            Some(SelectionRange {
+                range,
+                parent: parent.map(Box::new),
+            })
+        })
+        .unwrap_or_else(|| SelectionRange {
+            range: Range::new(position, position),
+            parent: None,
+        })
+}
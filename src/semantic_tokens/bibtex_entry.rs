@@ -0,0 +1,109 @@
+use super::{SemanticItem, SemanticItemKind};
+use futures_boxed::boxed;
+use texlab_protocol::SemanticTokensParams;
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BibtexEntrySemanticTokenProvider;
+
+impl FeatureProvider for BibtexEntrySemanticTokenProvider {
+    type Params = SemanticTokensParams;
+    type Output = Vec<SemanticItem>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<SemanticTokensParams>,
+    ) -> Vec<SemanticItem> {
+        let mut items = Vec::new();
+        if let SyntaxTree::Bibtex(tree) = &request.document().tree {
+            for declaration in &tree.root.children {
+                match declaration {
+                    BibtexDeclaration::Comment(_) => (),
+                    BibtexDeclaration::Preamble(preamble) => {
+                        items.push(SemanticItem::new(
+                            preamble.ty.range(),
+                            SemanticItemKind::EntryType,
+                        ));
+                    }
+                    BibtexDeclaration::String(string) => {
+                        items.push(SemanticItem::new(
+                            string.ty.range(),
+                            SemanticItemKind::EntryType,
+                        ));
+                    }
+                    BibtexDeclaration::Entry(entry) => {
+                        items.push(SemanticItem::new(
+                            entry.ty.range(),
+                            SemanticItemKind::EntryType,
+                        ));
+                        if entry.is_comment() {
+                            continue;
+                        }
+
+                        if let Some(key) = &entry.key {
+                            items.push(SemanticItem::declaration(
+                                key.range(),
+                                SemanticItemKind::CitationKey,
+                            ));
+                        }
+
+                        for field in &entry.fields {
+                            items.push(SemanticItem::new(
+                                field.name.range(),
+                                SemanticItemKind::FieldName,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::{Range, RangeExt};
+
+    #[test]
+    fn entry() {
+        let items = test_feature(
+            BibtexEntrySemanticTokenProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.bib",
+                    "@article{foo,\ntitle = {Bar}}",
+                )],
+                main_file: "foo.bib",
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            items,
+            vec![
+                SemanticItem::new(Range::new_simple(0, 0, 0, 8), SemanticItemKind::EntryType),
+                SemanticItem::declaration(
+                    Range::new_simple(0, 9, 0, 12),
+                    SemanticItemKind::CitationKey
+                ),
+                SemanticItem::new(Range::new_simple(1, 0, 1, 5), SemanticItemKind::FieldName),
+            ]
+        );
+    }
+
+    #[test]
+    fn latex() {
+        let items = test_feature(
+            BibtexEntrySemanticTokenProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "@article{foo,}")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(items.is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+use super::{SemanticItem, SemanticItemKind};
+use futures_boxed::boxed;
+use texlab_protocol::SemanticTokensParams;
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LatexCitationSemanticTokenProvider;
+
+impl FeatureProvider for LatexCitationSemanticTokenProvider {
+    type Params = SemanticTokensParams;
+    type Output = Vec<SemanticItem>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<SemanticTokensParams>,
+    ) -> Vec<SemanticItem> {
+        let mut items = Vec::new();
+        if let SyntaxTree::Latex(tree) = &request.document().tree {
+            for citation in &tree.citations {
+                for key in citation.keys() {
+                    items.push(SemanticItem::new(
+                        key.range(),
+                        SemanticItemKind::CitationKey,
+                    ));
+                }
+            }
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::{Range, RangeExt};
+
+    #[test]
+    fn citation() {
+        let items = test_feature(
+            LatexCitationSemanticTokenProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "\\cite{foo,bar}")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            items,
+            vec![
+                SemanticItem::new(Range::new_simple(0, 6, 0, 9), SemanticItemKind::CitationKey),
+                SemanticItem::new(
+                    Range::new_simple(0, 10, 0, 13),
+                    SemanticItemKind::CitationKey
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use super::{SemanticItem, SemanticItemKind};
+use futures_boxed::boxed;
+use std::collections::HashSet;
+use texlab_protocol::SemanticTokensParams;
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LatexCommandSemanticTokenProvider;
+
+impl FeatureProvider for LatexCommandSemanticTokenProvider {
+    type Params = SemanticTokensParams;
+    type Output = Vec<SemanticItem>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<SemanticTokensParams>,
+    ) -> Vec<SemanticItem> {
+        let mut items = Vec::new();
+        if let SyntaxTree::Latex(tree) = &request.document().tree {
+            let mut user_commands = HashSet::new();
+            for document in request.related_documents() {
+                if let SyntaxTree::Latex(tree) = &document.tree {
+                    for definition in &tree.command_definitions {
+                        user_commands.insert(definition.definition.name.text().to_owned());
+                    }
+                }
+            }
+
+            for command in &tree.commands {
+                let range = command.name.range();
+                let is_declaration = tree
+                    .command_definitions
+                    .iter()
+                    .any(|definition| definition.definition.name.range() == range);
+
+                let item = if is_declaration {
+                    SemanticItem::declaration(range, SemanticItemKind::UserCommand)
+                } else if user_commands.contains(command.name.text()) {
+                    SemanticItem::new(range, SemanticItemKind::UserCommand)
+                } else {
+                    SemanticItem::new(range, SemanticItemKind::Command)
+                };
+                items.push(item);
+            }
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::{Range, RangeExt};
+
+    #[test]
+    fn command() {
+        let items = test_feature(
+            LatexCommandSemanticTokenProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "\\foo\\bar")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            items,
+            vec![
+                SemanticItem::new(Range::new_simple(0, 0, 0, 4), SemanticItemKind::Command),
+                SemanticItem::new(Range::new_simple(0, 4, 0, 8), SemanticItemKind::Command),
+            ]
+        );
+    }
+
+    #[test]
+    fn user_command() {
+        let items = test_feature(
+            LatexCommandSemanticTokenProvider,
+            FeatureSpec {
+                files: vec![
+                    FeatureSpec::file("foo.tex", "\\include{bar}\n\\foo"),
+                    FeatureSpec::file("bar.tex", "\\newcommand{\\foo}{baz}"),
+                ],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            items,
+            vec![
+                SemanticItem::new(Range::new_simple(0, 0, 0, 8), SemanticItemKind::Command),
+                SemanticItem::new(Range::new_simple(1, 0, 1, 4), SemanticItemKind::UserCommand),
+            ]
+        );
+    }
+
+    #[test]
+    fn declaration() {
+        let items = test_feature(
+            LatexCommandSemanticTokenProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "\\newcommand{\\foo}{baz}")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            items,
+            vec![
+                SemanticItem::new(Range::new_simple(0, 0, 0, 11), SemanticItemKind::Command),
+                SemanticItem::declaration(
+                    Range::new_simple(0, 12, 0, 16),
+                    SemanticItemKind::UserCommand
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn bibtex() {
+        let items = test_feature(
+            LatexCommandSemanticTokenProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.bib",
+                    "@article{foo, title = {\\foo}}",
+                )],
+                main_file: "foo.bib",
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(items.is_empty());
+    }
+}
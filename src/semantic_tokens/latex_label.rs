@@ -0,0 +1,62 @@
+use super::{SemanticItem, SemanticItemKind};
+use futures_boxed::boxed;
+use texlab_protocol::SemanticTokensParams;
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LatexLabelSemanticTokenProvider;
+
+impl FeatureProvider for LatexLabelSemanticTokenProvider {
+    type Params = SemanticTokensParams;
+    type Output = Vec<SemanticItem>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<SemanticTokensParams>,
+    ) -> Vec<SemanticItem> {
+        let mut items = Vec::new();
+        if let SyntaxTree::Latex(tree) = &request.document().tree {
+            for label in &tree.structure.labels {
+                for name in label.names() {
+                    let item = match label.kind {
+                        LatexLabelKind::Definition => {
+                            SemanticItem::declaration(name.range(), SemanticItemKind::Label)
+                        }
+                        LatexLabelKind::Reference(_) => {
+                            SemanticItem::new(name.range(), SemanticItemKind::Label)
+                        }
+                    };
+                    items.push(item);
+                }
+            }
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::{Range, RangeExt};
+
+    #[test]
+    fn label() {
+        let items = test_feature(
+            LatexLabelSemanticTokenProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "\\label{foo}\n\\ref{foo}")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            items,
+            vec![
+                SemanticItem::declaration(Range::new_simple(0, 7, 0, 10), SemanticItemKind::Label),
+                SemanticItem::new(Range::new_simple(1, 5, 1, 8), SemanticItemKind::Label),
+            ]
+        );
+    }
+}
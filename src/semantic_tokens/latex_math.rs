@@ -0,0 +1,126 @@
+use super::{SemanticItem, SemanticItemKind};
+use futures_boxed::boxed;
+use std::sync::Arc;
+use texlab_protocol::{RangeExt, SemanticTokensParams};
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LatexMathSemanticTokenProvider;
+
+impl FeatureProvider for LatexMathSemanticTokenProvider {
+    type Params = SemanticTokensParams;
+    type Output = Vec<SemanticItem>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<SemanticTokensParams>,
+    ) -> Vec<SemanticItem> {
+        let mut items = Vec::new();
+        if let SyntaxTree::Latex(tree) = &request.document().tree {
+            if tree.math.inlines.is_empty() {
+                return items;
+            }
+
+            let mut analyzer = LatexWordAnalyzer::default();
+            analyzer.visit_root(Arc::clone(&tree.root));
+            for inline in &tree.math.inlines {
+                let range = inline.range();
+                items.push(SemanticItem::new(
+                    inline.left.range(),
+                    SemanticItemKind::Math,
+                ));
+                for word in analyzer
+                    .words
+                    .iter()
+                    .filter(|word| range.contains_exclusive(word.start()))
+                {
+                    items.push(SemanticItem::new(word.range(), SemanticItemKind::Math));
+                }
+                items.push(SemanticItem::new(
+                    inline.right.range(),
+                    SemanticItemKind::Math,
+                ));
+            }
+        }
+        items
+    }
+}
+
+#[derive(Debug, Default)]
+struct LatexWordAnalyzer {
+    words: Vec<LatexToken>,
+}
+
+impl LatexVisitor for LatexWordAnalyzer {
+    fn visit_root(&mut self, root: Arc<LatexRoot>) {
+        LatexWalker::walk_root(self, root);
+    }
+
+    fn visit_group(&mut self, group: Arc<LatexGroup>) {
+        LatexWalker::walk_group(self, group);
+    }
+
+    fn visit_command(&mut self, command: Arc<LatexCommand>) {
+        LatexWalker::walk_command(self, command);
+    }
+
+    fn visit_text(&mut self, text: Arc<LatexText>) {
+        self.words.extend(text.words.iter().cloned());
+        LatexWalker::walk_text(self, text);
+    }
+
+    fn visit_comma(&mut self, comma: Arc<LatexComma>) {
+        LatexWalker::walk_comma(self, comma);
+    }
+
+    fn visit_math(&mut self, math: Arc<LatexMath>) {
+        LatexWalker::walk_math(self, math);
+    }
+
+    fn visit_verbatim(&mut self, verbatim: Arc<LatexVerbatim>) {
+        LatexWalker::walk_verbatim(self, verbatim);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::Range;
+
+    #[test]
+    fn inline() {
+        let items = test_feature(
+            LatexMathSemanticTokenProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "foo $x + y$ bar")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(
+            items,
+            vec![
+                SemanticItem::new(Range::new_simple(0, 4, 0, 5), SemanticItemKind::Math),
+                SemanticItem::new(Range::new_simple(0, 5, 0, 6), SemanticItemKind::Math),
+                SemanticItem::new(Range::new_simple(0, 7, 0, 8), SemanticItemKind::Math),
+                SemanticItem::new(Range::new_simple(0, 9, 0, 10), SemanticItemKind::Math),
+                SemanticItem::new(Range::new_simple(0, 10, 0, 11), SemanticItemKind::Math),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_math() {
+        let items = test_feature(
+            LatexMathSemanticTokenProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "foo bar")],
+                main_file: "foo.tex",
+                ..FeatureSpec::default()
+            },
+        );
+        assert!(items.is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+mod bibtex_entry;
+mod latex_citation;
+mod latex_command;
+mod latex_label;
+mod latex_math;
+
+use self::bibtex_entry::BibtexEntrySemanticTokenProvider;
+use self::latex_citation::LatexCitationSemanticTokenProvider;
+use self::latex_command::LatexCommandSemanticTokenProvider;
+use self::latex_label::LatexLabelSemanticTokenProvider;
+use self::latex_math::LatexMathSemanticTokenProvider;
+use futures_boxed::boxed;
+use std::collections::BTreeMap;
+use texlab_protocol::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SemanticItemKind {
+    Command = 0,
+    UserCommand = 1,
+    Label = 2,
+    CitationKey = 3,
+    Math = 4,
+    EntryType = 5,
+    FieldName = 6,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SemanticItem {
+    pub range: Range,
+    pub kind: SemanticItemKind,
+    pub declaration: bool,
+}
+
+impl SemanticItem {
+    pub fn new(range: Range, kind: SemanticItemKind) -> Self {
+        Self {
+            range,
+            kind,
+            declaration: false,
+        }
+    }
+
+    pub fn declaration(range: Range, kind: SemanticItemKind) -> Self {
+        Self {
+            range,
+            kind,
+            declaration: true,
+        }
+    }
+}
+
+pub struct SemanticTokensProvider {
+    provider: ConcatProvider<SemanticTokensParams, SemanticItem>,
+}
+
+impl SemanticTokensProvider {
+    pub fn new() -> Self {
+        Self {
+            provider: ConcatProvider::new(vec![
+                Box::new(BibtexEntrySemanticTokenProvider),
+                Box::new(LatexCommandSemanticTokenProvider),
+                Box::new(LatexLabelSemanticTokenProvider),
+                Box::new(LatexCitationSemanticTokenProvider),
+                Box::new(LatexMathSemanticTokenProvider),
+            ]),
+        }
+    }
+
+    pub fn legend() -> SemanticTokensLegend {
+        SemanticTokensLegend {
+            token_types: vec![
+                SemanticTokenType::MACRO,
+                SemanticTokenType::FUNCTION,
+                SemanticTokenType::VARIABLE,
+                SemanticTokenType::PARAMETER,
+                // Math is highlighted like a literal of an embedded language.
+                SemanticTokenType::STRING,
+                SemanticTokenType::TYPE,
+                SemanticTokenType::PROPERTY,
+            ],
+            token_modifiers: vec![SemanticTokenModifier::DECLARATION],
+        }
+    }
+
+    /// Encodes the items relative to each other.
+    /// Items that span multiple lines are split into one token per line and
+    /// overlapping items are resolved in favor of the innermost item.
+    pub fn encode(items: Vec<SemanticItem>, text: &str, range: Option<Range>) -> SemanticTokens {
+        let line_lengths: Vec<u64> = text
+            .lines()
+            .map(|line| line.chars().map(|c| c.len_utf16() as u64).sum())
+            .collect();
+
+        let mut items_by_line: BTreeMap<u64, Vec<SemanticItem>> = BTreeMap::new();
+        for item in items {
+            for item in split_lines(item, &line_lengths) {
+                items_by_line
+                    .entry(item.range.start.line)
+                    .or_default()
+                    .push(item);
+            }
+        }
+
+        let mut data = Vec::new();
+        let mut previous = Position::new(0, 0);
+        for item in items_by_line
+            .into_iter()
+            .flat_map(|(_, items)| resolve_overlaps(items))
+        {
+            let start = item.range.start;
+            let end = item.range.end;
+            if let Some(range) = range {
+                if start < range.start || start > range.end {
+                    continue;
+                }
+            }
+
+            let delta_line = start.line - previous.line;
+            let delta_start = if delta_line == 0 {
+                start.character - previous.character
+            } else {
+                start.character
+            };
+
+            data.push(SemanticToken {
+                delta_line: delta_line as u32,
+                delta_start: delta_start as u32,
+                length: (end.character - start.character) as u32,
+                token_type: item.kind as u32,
+                token_modifiers_bitset: if item.declaration { 1 } else { 0 },
+            });
+            previous = start;
+        }
+
+        SemanticTokens {
+            result_id: None,
+            data,
+        }
+    }
+}
+
+/// Splits the item into one non-empty item per line.
+fn split_lines(item: SemanticItem, line_lengths: &[u64]) -> Vec<SemanticItem> {
+    let start = item.range.start;
+    let end = item.range.end;
+    (start.line..=end.line)
+        .map(|line| {
+            let start_character = if line == start.line {
+                start.character
+            } else {
+                0
+            };
+            let end_character = if line == end.line {
+                end.character
+            } else {
+                line_lengths.get(line as usize).copied().unwrap_or(0)
+            };
+            SemanticItem {
+                range: Range::new(
+                    Position::new(line, start_character),
+                    Position::new(line, end_character),
+                ),
+                ..item
+            }
+        })
+        .filter(|item| item.range.start.character < item.range.end.character)
+        .collect()
+}
+
+/// Removes the overlaps between the items of a single line.
+/// Shorter items take precedence, so that the surrounding items are split around them.
+fn resolve_overlaps(mut items: Vec<SemanticItem>) -> Vec<SemanticItem> {
+    items.sort_by_key(|item| item.range.end.character - item.range.start.character);
+    let mut resolved: Vec<SemanticItem> = Vec::new();
+    for item in items {
+        let mut pieces = vec![item];
+        for other in &resolved {
+            pieces = pieces
+                .into_iter()
+                .flat_map(|piece| subtract(piece, other.range))
+                .collect();
+        }
+        resolved.append(&mut pieces);
+    }
+    resolved.sort_by_key(|item| item.range.start);
+    resolved
+}
+
+/// Returns the parts of the item that are not covered by the given range of the same line.
+fn subtract(item: SemanticItem, range: Range) -> Vec<SemanticItem> {
+    if range.end <= item.range.start || range.start >= item.range.end {
+        return vec![item];
+    }
+
+    let mut parts = Vec::new();
+    if item.range.start < range.start {
+        parts.push(SemanticItem {
+            range: Range::new(item.range.start, range.start),
+            ..item
+        });
+    }
+
+    if range.end < item.range.end {
+        parts.push(SemanticItem {
+            range: Range::new(range.end, item.range.end),
+            ..item
+        });
+    }
+    parts
+}
+
+impl Default for SemanticTokensProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeatureProvider for SemanticTokensProvider {
+    type Params = SemanticTokensParams;
+    type Output = Vec<SemanticItem>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<SemanticTokensParams>,
+    ) -> Vec<SemanticItem> {
+        self.provider.execute(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_relative() {
+        let items = vec![
+            SemanticItem::new(Range::new_simple(1, 2, 1, 6), SemanticItemKind::Command),
+            SemanticItem::declaration(Range::new_simple(0, 7, 0, 10), SemanticItemKind::Label),
+            SemanticItem::new(Range::new_simple(1, 7, 1, 10), SemanticItemKind::Label),
+        ];
+        let tokens = SemanticTokensProvider::encode(items, "", None);
+        assert_eq!(
+            tokens.data,
+            vec![
+                SemanticToken {
+                    delta_line: 0,
+                    delta_start: 7,
+                    length: 3,
+                    token_type: 2,
+                    token_modifiers_bitset: 1,
+                },
+                SemanticToken {
+                    delta_line: 1,
+                    delta_start: 2,
+                    length: 4,
+                    token_type: 0,
+                    token_modifiers_bitset: 0,
+                },
+                SemanticToken {
+                    delta_line: 0,
+                    delta_start: 5,
+                    length: 3,
+                    token_type: 2,
+                    token_modifiers_bitset: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_multiline() {
+        let items = vec![SemanticItem::new(
+            Range::new_simple(0, 5, 2, 3),
+            SemanticItemKind::Label,
+        )];
+        let tokens = SemanticTokensProvider::encode(items, "\\ref{foo\n\nbar}", None);
+        assert_eq!(
+            tokens.data,
+            vec![
+                SemanticToken {
+                    delta_line: 0,
+                    delta_start: 5,
+                    length: 3,
+                    token_type: 2,
+                    token_modifiers_bitset: 0,
+                },
+                SemanticToken {
+                    delta_line: 2,
+                    delta_start: 0,
+                    length: 3,
+                    token_type: 2,
+                    token_modifiers_bitset: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_overlapping() {
+        let items = vec![
+            SemanticItem::new(Range::new_simple(0, 0, 0, 10), SemanticItemKind::Math),
+            SemanticItem::new(Range::new_simple(0, 3, 0, 7), SemanticItemKind::Command),
+            SemanticItem::new(Range::new_simple(0, 3, 0, 5), SemanticItemKind::Label),
+        ];
+        let tokens = SemanticTokensProvider::encode(items, "", None);
+        let ranges: Vec<_> = tokens
+            .data
+            .iter()
+            .map(|token| (token.delta_start, token.length, token.token_type))
+            .collect();
+        assert_eq!(ranges, vec![(0, 3, 4), (3, 2, 2), (2, 2, 0), (2, 3, 4)]);
+    }
+
+    #[test]
+    fn encode_range() {
+        let items = vec![
+            SemanticItem::new(Range::new_simple(0, 0, 0, 4), SemanticItemKind::Command),
+            SemanticItem::new(Range::new_simple(2, 0, 2, 4), SemanticItemKind::Command),
+        ];
+        let tokens = SemanticTokensProvider::encode(items, "", Some(Range::new_simple(1, 0, 3, 0)));
+        assert_eq!(
+            tokens.data,
+            vec![SemanticToken {
+                delta_line: 2,
+                delta_start: 0,
+                length: 4,
+                token_type: 0,
+                token_modifiers_bitset: 0,
+            }]
+        );
+    }
+}
@@ -1,24 +1,39 @@
 use crate::action::{Action, ActionManager, LintReason};
 use crate::build::*;
+use crate::cache::DocumentCache;
+use crate::clean;
+use crate::code_action::CodeActionProvider;
+use crate::code_lens::CodeLensProvider;
 use crate::config::ConfigStrategy;
+use crate::config_file::{self, ConfigFileManager, CONFIG_FILE_NAMES};
 use crate::definition::DefinitionProvider;
 use crate::diagnostics::DiagnosticsManager;
+use crate::folder::FolderManager;
 use crate::folding::FoldingProvider;
 use crate::forward_search;
 use crate::highlight::HighlightProvider;
 use crate::link::LinkProvider;
+use crate::on_type_formatting::OnTypeFormattingProvider;
+use crate::progress;
 use crate::reference::ReferenceProvider;
 use crate::rename::{PrepareRenameProvider, RenameProvider};
-use crate::workspace_manager::{WorkspaceLoadError, WorkspaceManager};
+use crate::selection_range::SelectionRangeProvider;
+use crate::semantic_tokens::SemanticTokensProvider;
+use crate::signature_help::SignatureHelpProvider;
+use crate::todo::TodoProvider;
+use crate::watcher::{FileWatcher, WATCHED_FILES_GLOB};
+use crate::workspace_manager::{WorkspaceLoadError, WorkspaceManager, WorkspaceUpdateError};
 use futures::lock::Mutex;
 use futures_boxed::boxed;
 use jsonrpc::server::{Middleware, Result};
 use jsonrpc_derive::{jsonrpc_method, jsonrpc_server};
 use log::*;
 use once_cell::sync::{Lazy, OnceCell};
+use serde::de::DeserializeOwned;
 use std::ffi::OsStr;
 use std::fs;
 use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use texlab_citeproc::render_citation;
 use texlab_completion::{CompletionItemData, CompletionProvider};
@@ -28,17 +43,30 @@ use texlab_protocol::*;
 use texlab_symbol::SymbolProvider;
 use texlab_syntax::*;
 use texlab_workspace::*;
+use tokio::task;
 use walkdir::WalkDir;
 
+const BUILD_COMMAND: &str = "texlab.build";
+
+const FORWARD_SEARCH_COMMAND: &str = "texlab.forwardSearch";
+
+const CLEAN_AUXILIARY_COMMAND: &str = "texlab.cleanAuxiliary";
+
 pub struct LatexLspServer<C> {
     client: Arc<C>,
     client_capabilities: OnceCell<Arc<ClientCapabilities>>,
     distribution: Arc<Box<dyn Distribution>>,
+    cache: Arc<DocumentCache>,
     config_strategy: OnceCell<Box<dyn ConfigStrategy>>,
+    folder_manager: FolderManager,
+    config_file_manager: ConfigFileManager,
     build_manager: BuildManager<C>,
     workspace_manager: WorkspaceManager,
     action_manager: ActionManager,
+    file_watcher: FileWatcher,
     diagnostics_manager: Mutex<DiagnosticsManager>,
+    code_action_provider: CodeActionProvider,
+    code_lens_provider: CodeLensProvider,
     completion_provider: CompletionProvider,
     definition_provider: DefinitionProvider,
     folding_provider: FoldingProvider,
@@ -46,23 +74,39 @@ pub struct LatexLspServer<C> {
     symbol_provider: SymbolProvider,
     hover_provider: HoverProvider,
     link_provider: LinkProvider,
-    reference_provider: ReferenceProvider,
+    on_type_formatting_provider: OnTypeFormattingProvider,
+    reference_provider: Arc<ReferenceProvider>,
     prepare_rename_provider: PrepareRenameProvider,
     rename_provider: RenameProvider,
+    selection_range_provider: SelectionRangeProvider,
+    semantic_tokens_provider: SemanticTokensProvider,
+    signature_help_provider: SignatureHelpProvider,
+    todo_provider: TodoProvider,
 }
 
 #[jsonrpc_server]
 impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
-    pub fn new(client: Arc<C>, distribution: Arc<Box<dyn Distribution>>) -> Self {
+    pub fn new(
+        client: Arc<C>,
+        distribution: Arc<Box<dyn Distribution>>,
+        cache: Arc<DocumentCache>,
+    ) -> Self {
+        let reference_provider = Arc::new(ReferenceProvider::new());
         Self {
             client: Arc::clone(&client),
             client_capabilities: OnceCell::new(),
             distribution: Arc::clone(&distribution),
+            cache: Arc::clone(&cache),
             config_strategy: OnceCell::new(),
+            folder_manager: FolderManager::default(),
+            config_file_manager: ConfigFileManager::default(),
             build_manager: BuildManager::new(client),
-            workspace_manager: WorkspaceManager::new(distribution),
+            workspace_manager: WorkspaceManager::new(distribution, cache),
             action_manager: ActionManager::default(),
+            file_watcher: FileWatcher::default(),
             diagnostics_manager: Mutex::new(DiagnosticsManager::default()),
+            code_action_provider: CodeActionProvider::new(),
+            code_lens_provider: CodeLensProvider::new(Arc::clone(&reference_provider)),
             completion_provider: CompletionProvider::new(),
             definition_provider: DefinitionProvider::new(),
             folding_provider: FoldingProvider::new(),
@@ -70,9 +114,14 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
             symbol_provider: SymbolProvider::new(),
             hover_provider: HoverProvider::new(),
             link_provider: LinkProvider::new(),
-            reference_provider: ReferenceProvider::new(),
+            on_type_formatting_provider: OnTypeFormattingProvider::new(),
+            reference_provider,
             prepare_rename_provider: PrepareRenameProvider::new(),
             rename_provider: RenameProvider::new(),
+            selection_range_provider: SelectionRangeProvider::new(),
+            semantic_tokens_provider: SemanticTokensProvider::new(),
+            signature_help_provider: SignatureHelpProvider::new(),
+            todo_provider: TodoProvider,
         }
     }
 
@@ -93,6 +142,21 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
         let config_strategy = ConfigStrategy::select(&params.capabilities, client);
         let _ = self.config_strategy.set(config_strategy);
 
+        let roots = match &params.workspace_folders {
+            Some(folders) => folders
+                .iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect(),
+            None => params
+                .root_uri
+                .as_ref()
+                .and_then(|uri| uri.to_file_path().ok())
+                .or_else(|| params.root_path.as_ref().map(PathBuf::from))
+                .into_iter()
+                .collect(),
+        };
+        self.folder_manager.set(roots);
+
         self.client_capabilities
             .set(Arc::new(params.capabilities))
             .unwrap();
@@ -100,7 +164,7 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
             text_document_sync: Some(TextDocumentSyncCapability::Options(
                 TextDocumentSyncOptions {
                     open_close: Some(true),
-                    change: Some(TextDocumentSyncKind::Full),
+                    change: Some(TextDocumentSyncKind::Incremental),
                     will_save: None,
                     will_save_wait_until: None,
                     save: Some(SaveOptions {
@@ -121,7 +185,11 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
                 ]),
                 work_done_progress_options: WorkDoneProgressOptions::default(),
             }),
-            signature_help_provider: None,
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(vec!["{".to_owned()]),
+                retrigger_characters: Some(vec!["}".to_owned()]),
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            }),
             definition_provider: Some(true),
             type_definition_provider: None,
             implementation_provider: None,
@@ -129,11 +197,16 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
             document_highlight_provider: Some(true),
             document_symbol_provider: Some(true),
             workspace_symbol_provider: Some(true),
-            code_action_provider: None,
-            code_lens_provider: None,
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            code_lens_provider: Some(CodeLensOptions {
+                resolve_provider: Some(false),
+            }),
             document_formatting_provider: Some(true),
-            document_range_formatting_provider: None,
-            document_on_type_formatting_provider: None,
+            document_range_formatting_provider: Some(true),
+            document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                first_trigger_character: "}".to_owned(),
+                more_trigger_character: None,
+            }),
             rename_provider: Some(RenameProviderCapability::Options(RenameOptions {
                 prepare_provider: Some(true),
                 work_done_progress_options: WorkDoneProgressOptions::default(),
@@ -144,13 +217,34 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
             }),
             color_provider: None,
             folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
-            execute_command_provider: None,
-            workspace: None,
-            selection_range_provider: None,
+            execute_command_provider: Some(ExecuteCommandOptions {
+                commands: vec![
+                    BUILD_COMMAND.to_owned(),
+                    FORWARD_SEARCH_COMMAND.to_owned(),
+                    CLEAN_AUXILIARY_COMMAND.to_owned(),
+                ],
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            }),
+            workspace: Some(WorkspaceCapability {
+                workspace_folders: Some(WorkspaceFolderCapability {
+                    supported: Some(true),
+                    change_notifications: Some(WorkspaceFolderCapabilityChangeNotifications::Bool(
+                        true,
+                    )),
+                }),
+            }),
+            selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
             declaration_provider: None,
             semantic_highlighting: None,
             call_hierarchy_provider: None,
-            semantic_tokens_provider: None,
+            semantic_tokens_provider: Some(
+                SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                    legend: SemanticTokensProvider::legend(),
+                    range_provider: Some(true),
+                    document_provider: Some(SemanticTokensDocumentProvider::Bool(true)),
+                }),
+            ),
             experimental: None,
         };
 
@@ -167,13 +261,19 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
     #[jsonrpc_method("initialized", kind = "notification")]
     pub async fn initialized(&self, _params: InitializedParams) {
         self.action_manager.push(Action::RegisterCapabilities);
+        self.action_manager.push(Action::RestoreCache);
         self.action_manager.push(Action::PublishDiagnostics);
         self.action_manager.push(Action::LoadDistribution);
         self.action_manager.push(Action::LoadConfiguration);
+        for folder in self.folder_manager.get() {
+            self.action_manager.push(Action::LoadFolder(folder));
+        }
     }
 
     #[jsonrpc_method("shutdown", kind = "request")]
     pub async fn shutdown(&self, _params: ()) -> Result<()> {
+        let cache = Arc::clone(&self.cache);
+        task::spawn_blocking(move || cache.save()).await.unwrap();
         Ok(())
     }
 
@@ -185,24 +285,32 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
 
     #[jsonrpc_method("textDocument/didOpen", kind = "notification")]
     pub async fn did_open(&self, params: DidOpenTextDocumentParams) {
-        let uri = params.text_document.uri.clone();
-        let options = self.configuration(false).await;
-        self.workspace_manager.add(params.text_document, &options);
-        self.action_manager
-            .push(Action::DetectRoot(uri.clone().into()));
+        let uri: Uri = params.text_document.uri.clone().into();
+        let options = self.configuration(&uri, false).await;
+        self.workspace_manager
+            .add(params.text_document, &options)
+            .await;
+        self.detect_children().await;
+        self.update_build_diagnostics().await;
+        self.action_manager.push(Action::DetectRoot(uri.clone()));
         self.action_manager
-            .push(Action::RunLinter(Uri::from(uri), LintReason::Save));
+            .push(Action::RunLinter(uri, LintReason::Save));
         self.action_manager.push(Action::PublishDiagnostics);
     }
 
     #[jsonrpc_method("textDocument/didChange", kind = "notification")]
     pub async fn did_change(&self, params: DidChangeTextDocumentParams) {
-        let options = self.configuration(false).await;
-        for change in params.content_changes {
-            let uri = params.text_document.uri.clone();
-            self.workspace_manager
-                .update(uri.into(), change.text, &options);
+        let uri: Uri = params.text_document.uri.clone().into();
+        let options = self.configuration(&uri, false).await;
+        let result = self
+            .workspace_manager
+            .update(&uri, params.content_changes, &options)
+            .await;
+
+        if let Err(WorkspaceUpdateError::DocumentNotFound) = result {
+            warn!("Document not found: {}", uri);
         }
+        self.detect_children().await;
         self.action_manager.push(Action::RunLinter(
             params.text_document.uri.into(),
             LintReason::Change,
@@ -222,7 +330,10 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
     }
 
     #[jsonrpc_method("textDocument/didClose", kind = "notification")]
-    pub async fn did_close(&self, _params: DidCloseTextDocumentParams) {}
+    pub async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri.into();
+        self.workspace_manager.close(&uri);
+    }
 
     #[jsonrpc_method("workspace/didChangeConfiguration", kind = "notification")]
     pub async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
@@ -230,6 +341,32 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
             .push(Action::UpdateConfiguration(params.settings));
     }
 
+    #[jsonrpc_method("workspace/didChangeWatchedFiles", kind = "notification")]
+    pub async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        self.update_files(params.changes).await;
+    }
+
+    #[jsonrpc_method("workspace/didChangeWorkspaceFolders", kind = "notification")]
+    pub async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        for folder in params.event.removed {
+            if let Ok(path) = folder.uri.to_file_path() {
+                self.folder_manager.remove(&path);
+                self.workspace_manager.unload(|document_path| {
+                    self.folder_manager.find(document_path).is_none()
+                        && document_path.starts_with(&path)
+                });
+            }
+        }
+
+        for folder in params.event.added {
+            if let Ok(path) = folder.uri.to_file_path() {
+                self.folder_manager.add(path.clone());
+                self.action_manager.push(Action::LoadFolder(path));
+            }
+        }
+        self.action_manager.push(Action::PublishDiagnostics);
+    }
+
     #[jsonrpc_method("window/workDoneProgress/cancel", kind = "notification")]
     pub async fn work_done_progress_cancel(&self, params: WorkDoneProgressCancelParams) {
         self.action_manager.push(Action::CancelBuild(params.token));
@@ -279,6 +416,18 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
         Ok(hover)
     }
 
+    #[jsonrpc_method("textDocument/signatureHelp", kind = "request")]
+    pub async fn signature_help(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<SignatureHelp>> {
+        let request = self
+            .make_feature_request(params.text_document.as_uri(), params)
+            .await?;
+        let help = self.signature_help_provider.execute(&request).await;
+        Ok(help)
+    }
+
     #[jsonrpc_method("textDocument/definition", kind = "request")]
     pub async fn definition(
         &self,
@@ -331,15 +480,35 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
         let distribution = Arc::clone(&self.distribution);
         let client_capabilities = Arc::clone(&self.client_capabilities.get().unwrap());
         let workspace = self.workspace_manager.get();
-        let options = self.configuration(true).await;
-        let symbols = texlab_symbol::workspace_symbols(
-            distribution,
-            client_capabilities,
-            workspace,
-            &options,
-            &params,
-        )
-        .await;
+        let folders = self.folder_manager.get();
+        let scopes = folders.iter().map(Some).chain(std::iter::once(None));
+        let mut symbols = Vec::new();
+        for folder in scopes {
+            let scope = folder.and_then(|folder| Uri::from_file_path(folder).ok());
+            let options = self.scope_configuration(scope, true).await;
+            let workspace = self
+                .folder_manager
+                .restrict(&workspace, folder.map(PathBuf::as_path));
+            let folder_symbols = texlab_symbol::workspace_symbols(
+                Arc::clone(&distribution),
+                Arc::clone(&client_capabilities),
+                workspace,
+                &options,
+                &params,
+            )
+            .await;
+
+            // Shared documents are part of every folder but only reported once.
+            symbols.extend(folder_symbols.into_iter().filter(|symbol| {
+                let owner = symbol
+                    .location
+                    .uri
+                    .to_file_path()
+                    .ok()
+                    .and_then(|path| self.folder_manager.find(&path));
+                owner.as_ref() == folder
+            }));
+        }
         Ok(symbols)
     }
 
@@ -376,36 +545,113 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
         let request = self
             .make_feature_request(params.text_document.as_uri(), params)
             .await?;
-        let mut edits = Vec::new();
-        if let SyntaxTree::Bibtex(tree) = &request.document().tree {
-            let options = self
-                .configuration(true)
-                .await
-                .bibtex
-                .and_then(|opts| opts.formatting)
-                .unwrap_or_default();
-
-            let params = BibtexFormattingParams {
-                tab_size: request.params.options.tab_size as usize,
-                insert_spaces: request.params.options.insert_spaces,
-                options,
-            };
+        let edits = match &request.document().tree {
+            SyntaxTree::Bibtex(tree) => {
+                self.format_bibtex(&request.options, tree, &request.params.options, None)
+                    .await
+            }
+            SyntaxTree::Latex(tree) => {
+                let text = &request.document().text;
+                self.format_latex(&request.options, text, tree, &request.params.options)
+                    .await
+            }
+        };
+        Ok(edits)
+    }
 
-            for declaration in &tree.root.children {
-                let should_format = match declaration {
-                    BibtexDeclaration::Comment(_) => false,
-                    BibtexDeclaration::Preamble(_) | BibtexDeclaration::String(_) => true,
-                    BibtexDeclaration::Entry(entry) => !entry.is_comment(),
-                };
-                if should_format {
-                    let text = format_declaration(&declaration, &params);
-                    edits.push(TextEdit::new(declaration.range(), text));
-                }
+    #[jsonrpc_method("textDocument/rangeFormatting", kind = "request")]
+    pub async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Vec<TextEdit>> {
+        let request = self
+            .make_feature_request(params.text_document.as_uri(), params)
+            .await?;
+        let edits = match &request.document().tree {
+            SyntaxTree::Bibtex(tree) => {
+                let range = Some(request.params.range);
+                self.format_bibtex(&request.options, tree, &request.params.options, range)
+                    .await
             }
-        }
+            SyntaxTree::Latex(_) => Vec::new(),
+        };
         Ok(edits)
     }
 
+    #[jsonrpc_method("textDocument/onTypeFormatting", kind = "request")]
+    pub async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Result<Vec<TextEdit>> {
+        let request = self
+            .make_feature_request(params.text_document_position.as_uri(), params)
+            .await?;
+        let edits = self.on_type_formatting_provider.execute(&request).await;
+        Ok(edits)
+    }
+
+    #[jsonrpc_method("textDocument/codeAction", kind = "request")]
+    pub async fn code_action(&self, params: CodeActionParams) -> Result<CodeActionResponse> {
+        let request = self
+            .make_feature_request(params.text_document.as_uri(), params)
+            .await?;
+        let actions = self.code_action_provider.execute(&request).await;
+        Ok(actions
+            .into_iter()
+            .map(CodeActionOrCommand::CodeAction)
+            .collect())
+    }
+
+    #[jsonrpc_method("textDocument/codeLens", kind = "request")]
+    pub async fn code_lens(&self, params: CodeLensParams) -> Result<Vec<CodeLens>> {
+        let request = self
+            .make_feature_request(params.text_document.as_uri(), params)
+            .await?;
+        let lenses = self.code_lens_provider.execute(&request).await;
+        Ok(lenses)
+    }
+
+    #[jsonrpc_method("textDocument/selectionRange", kind = "request")]
+    pub async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Vec<SelectionRange>> {
+        let request = self
+            .make_feature_request(params.text_document.as_uri(), params)
+            .await?;
+        let selections = self.selection_range_provider.execute(&request).await;
+        Ok(selections)
+    }
+
+    #[jsonrpc_method("textDocument/semanticTokens", kind = "request")]
+    pub async fn semantic_tokens(&self, params: SemanticTokensParams) -> Result<SemanticTokens> {
+        let request = self
+            .make_feature_request(params.text_document.as_uri(), params)
+            .await?;
+        let items = self.semantic_tokens_provider.execute(&request).await;
+        let text = &request.document().text;
+        Ok(SemanticTokensProvider::encode(items, text, None))
+    }
+
+    #[jsonrpc_method("textDocument/semanticTokens/range", kind = "request")]
+    pub async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<SemanticTokens> {
+        let range = params.range;
+        let params = SemanticTokensParams {
+            text_document: params.text_document,
+            work_done_progress_params: params.work_done_progress_params,
+            partial_result_params: params.partial_result_params,
+        };
+        let request = self
+            .make_feature_request(params.text_document.as_uri(), params)
+            .await?;
+        let items = self.semantic_tokens_provider.execute(&request).await;
+        let text = &request.document().text;
+        Ok(SemanticTokensProvider::encode(items, text, Some(range)))
+    }
+
     #[jsonrpc_method("textDocument/prepareRename", kind = "request")]
     pub async fn prepare_rename(
         &self,
@@ -439,7 +685,7 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
         let request = self
             .make_feature_request(params.text_document.as_uri(), params)
             .await?;
-        let options = self.configuration(true).await.latex.unwrap_or_default();
+        let options = request.options.latex.clone().unwrap_or_default();
         let result = self.build_manager.build(request, options).await;
         Ok(result)
     }
@@ -452,7 +698,7 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
         let request = self
             .make_feature_request(params.text_document.as_uri(), params)
             .await?;
-        let options = self.configuration(true).await;
+        let options = request.options.clone();
 
         match request.document().uri.to_file_path() {
             Ok(tex_file) => {
@@ -471,14 +717,165 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
         }
     }
 
-    async fn configuration(&self, fetch: bool) -> Options {
-        if let Some(strategy) = self.config_strategy.get() {
-            strategy.get(fetch).await
-        } else {
-            Options::default()
+    #[jsonrpc_method("texlab/todos", kind = "request")]
+    pub async fn todos(&self, params: TodoParams) -> Result<Vec<Todo>> {
+        let request = self
+            .make_feature_request(params.text_document.as_uri(), params)
+            .await?;
+        let todos = self.todo_provider.execute(&request).await;
+        Ok(todos)
+    }
+
+    #[jsonrpc_method("workspace/executeCommand", kind = "request")]
+    pub async fn execute_command(&self, params: ExecuteCommandParams) -> Result<serde_json::Value> {
+        match params.command.as_str() {
+            BUILD_COMMAND => {
+                let text_document = Self::command_argument(&params)?;
+                let result = self.build(BuildParams { text_document }).await?;
+                Ok(serde_json::to_value(result).unwrap())
+            }
+            FORWARD_SEARCH_COMMAND => {
+                let params = Self::command_argument(&params)?;
+                let result = self.forward_search(params).await?;
+                Ok(serde_json::to_value(result).unwrap())
+            }
+            CLEAN_AUXILIARY_COMMAND => {
+                let text_document: TextDocumentIdentifier = Self::command_argument(&params)?;
+                let request = self
+                    .make_feature_request(text_document.as_uri(), text_document)
+                    .await?;
+                let options = &request.options;
+                let parent = request
+                    .workspace()
+                    .find_parent(&request.document().uri, options)
+                    .unwrap_or_else(|| Arc::clone(&request.view.document));
+                let parent = parent
+                    .uri
+                    .to_file_path()
+                    .map_err(|()| "Unable to clean a document that is not a local file")?;
+                let removed_files = clean::clean(&parent, options).await;
+                Ok(serde_json::to_value(removed_files).unwrap())
+            }
+            _ => Err(format!("Unknown command: {}", params.command)),
         }
     }
 
+    fn command_argument<T: DeserializeOwned>(params: &ExecuteCommandParams) -> Result<T> {
+        params
+            .arguments
+            .get(0)
+            .and_then(|argument| serde_json::from_value(argument.clone()).ok())
+            .ok_or_else(|| format!("Invalid arguments for command: {}", params.command))
+    }
+
+    /// Returns the options of the document.
+    /// The client resolves its options with the document as the scope.
+    async fn configuration(&self, uri: &Uri, fetch: bool) -> Options {
+        let path = uri.to_file_path().ok();
+        let options = self.client_configuration(Some(uri.clone()), fetch).await;
+        self.merge_file_configuration(path, options)
+    }
+
+    async fn scope_configuration(&self, scope: Option<Uri>, fetch: bool) -> Options {
+        let path = scope.as_ref().and_then(|uri| uri.to_file_path().ok());
+        let options = self.client_configuration(scope, fetch).await;
+        self.merge_file_configuration(path, options)
+    }
+
+    async fn client_configuration(&self, scope: Option<Uri>, fetch: bool) -> Options {
+        match self.config_strategy.get() {
+            Some(strategy) => strategy.get(scope, fetch).await,
+            None => Options::default(),
+        }
+    }
+
+    fn merge_file_configuration(&self, path: Option<PathBuf>, options: Options) -> Options {
+        let file_options = path.and_then(|path| {
+            let ceiling = self.folder_manager.find(&path);
+            self.config_file_manager.find(&path, ceiling.as_deref())
+        });
+
+        match file_options {
+            Some(file_options) => config_file::merge(file_options, options),
+            None => options,
+        }
+    }
+
+    async fn format_bibtex(
+        &self,
+        settings: &Options,
+        tree: &BibtexSyntaxTree,
+        options: &FormattingOptions,
+        range: Option<Range>,
+    ) -> Vec<TextEdit> {
+        let params = BibtexFormattingParams {
+            tab_size: options.tab_size as usize,
+            insert_spaces: options.insert_spaces,
+            options: settings
+                .bibtex
+                .as_ref()
+                .and_then(|opts| opts.formatting.clone())
+                .unwrap_or_default(),
+        };
+
+        let mut edits = Vec::new();
+        for declaration in &tree.root.children {
+            let should_format = match declaration {
+                BibtexDeclaration::Comment(_) => false,
+                BibtexDeclaration::Preamble(_) | BibtexDeclaration::String(_) => true,
+                BibtexDeclaration::Entry(entry) => !entry.is_comment(),
+            };
+
+            let in_range = range.map_or(true, |range| {
+                declaration.start() <= range.end && declaration.end() >= range.start
+            });
+
+            if should_format && in_range {
+                let text = format_declaration(&declaration, &params);
+                edits.push(TextEdit::new(declaration.range(), text));
+            }
+        }
+        edits
+    }
+
+    async fn format_latex(
+        &self,
+        settings: &Options,
+        text: &str,
+        tree: &LatexSyntaxTree,
+        options: &FormattingOptions,
+    ) -> Vec<TextEdit> {
+        let params = LatexFormattingParams {
+            tab_size: options.tab_size as usize,
+            insert_spaces: options.insert_spaces,
+            options: settings
+                .latex
+                .as_ref()
+                .and_then(|opts| opts.formatting.clone())
+                .unwrap_or_default(),
+            verbatim_environments: settings
+                .latex
+                .as_ref()
+                .and_then(|opts| opts.verbatim_environments.clone())
+                .unwrap_or_default(),
+        };
+
+        let new_text = format_latex(text, tree, &params);
+        if new_text == text {
+            return Vec::new();
+        }
+
+        let last_line = text.split('\n').last().unwrap_or_default();
+        let end = Position::new(
+            text.split('\n').count() as u64 - 1,
+            last_line.encode_utf16().count() as u64,
+        );
+        vec![TextEdit::new(
+            Range::new(Position::new(0, 0), end),
+            new_text,
+        )]
+    }
+
     async fn make_feature_request<P>(&self, uri: Uri, params: P) -> Result<FeatureRequest<P>> {
         let workspace = self.workspace_manager.get();
         let client_capabilities = self
@@ -487,7 +884,12 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
             .expect("Failed to retrieve client capabilities");
 
         if let Some(document) = workspace.find(&uri) {
-            let options = self.configuration(true).await;
+            let options = self.configuration(&uri, true).await;
+            let folder = uri
+                .to_file_path()
+                .ok()
+                .and_then(|path| self.folder_manager.find(&path));
+            let workspace = self.folder_manager.restrict(&workspace, folder.as_deref());
             Ok(FeatureRequest {
                 params,
                 view: DocumentView::new(workspace, document, &options),
@@ -501,25 +903,96 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
         }
     }
 
+    async fn report_progress<F, T>(&self, title: &str, future: F) -> T
+    where
+        F: Future<Output = T>,
+    {
+        let capabilities = self.client_capabilities.get().unwrap();
+        if !capabilities.has_work_done_progress_support() {
+            return future.await;
+        }
+
+        let token = progress::token("progress");
+        let begin = WorkDoneProgressBegin {
+            title: title.into(),
+            cancellable: Some(false),
+            message: None,
+            percentage: None,
+        };
+        let has_progress = progress::begin(self.client.as_ref(), token.clone(), begin).await;
+        let result = future.await;
+        if has_progress {
+            progress::end(self.client.as_ref(), token).await;
+        }
+        result
+    }
+
+    /// Loads the given files and reports the progress if there are any.
+    async fn index_files(
+        &self,
+        paths: Vec<PathBuf>,
+        options: &Options,
+    ) -> Vec<std::result::Result<(), WorkspaceLoadError>> {
+        if paths.is_empty() {
+            return Vec::new();
+        }
+
+        self.report_progress(
+            "Indexing project",
+            self.workspace_manager.load_all(paths, options),
+        )
+        .await
+    }
+
     async fn detect_children(&self) {
-        let options = self.configuration(false).await;
-        loop {
-            let mut changed = false;
-
-            let workspace = self.workspace_manager.get();
-            for path in workspace.unresolved_includes(&options) {
-                if path.exists() {
-                    changed |= self.workspace_manager.load(&path, &options).is_ok();
-                }
-            }
+        let folders = self.folder_manager.get();
+        let scopes = folders.iter().map(Some).chain(std::iter::once(None));
+        for folder in scopes {
+            let scope = folder.and_then(|folder| Uri::from_file_path(folder).ok());
+            let options = self.scope_configuration(scope, false).await;
+            loop {
+                let workspace = self.workspace_manager.get();
+                let workspace = self
+                    .folder_manager
+                    .restrict(&workspace, folder.map(PathBuf::as_path));
+                let paths: Vec<_> = workspace
+                    .unresolved_includes(&options)
+                    .into_iter()
+                    .filter(|path| path.exists())
+                    .collect();
 
-            if !changed {
-                break;
+                let results = self.index_files(paths, &options).await;
+                if !results.iter().any(std::result::Result::is_ok) {
+                    break;
+                }
             }
         }
     }
 
-    fn update_document(
+    async fn load_folder(&self, folder: &Path) {
+        let scope = Uri::from_file_path(folder).ok();
+        let options = self.scope_configuration(scope, true).await;
+        let paths = WalkDir::new(folder)
+            .into_iter()
+            .filter_entry(|entry| !options.is_excluded(entry.path()))
+            .filter_map(std::result::Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .and_then(OsStr::to_str)
+                    .and_then(Language::by_extension)
+                    .is_some()
+            })
+            .map(|entry| entry.into_path())
+            .collect();
+
+        self.index_files(paths, &options).await;
+        self.detect_children().await;
+    }
+
+    async fn update_document(
         &self,
         document: &Document,
         options: &Options,
@@ -531,24 +1004,108 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
         let path = document.uri.to_file_path().unwrap();
         let data = fs::metadata(&path).map_err(WorkspaceLoadError::IO)?;
         if data.modified().map_err(WorkspaceLoadError::IO)? > document.modified {
-            self.workspace_manager.load(&path, &options)
+            self.workspace_manager.load(&path, &options).await
         } else {
             Ok(())
         }
     }
 
+    async fn update_files(&self, events: Vec<FileEvent>) {
+        let workspace = self.workspace_manager.get();
+        let mut log_changed = false;
+        let mut config_changed = false;
+        let mut documents_deleted = false;
+        for event in events {
+            let uri: Uri = event.uri.into();
+            let path = uri.to_file_path().ok();
+            if path.as_deref().map_or(false, config_file::is_config_file) {
+                config_changed = true;
+                continue;
+            }
+
+            let is_log = path
+                .and_then(|path| path.extension().map(|ext| ext == "log"))
+                .unwrap_or(false);
+
+            if is_log {
+                log_changed = true;
+                continue;
+            }
+
+            match event.typ {
+                FileChangeType::Created | FileChangeType::Changed => {
+                    if let Some(document) = workspace.find(&uri) {
+                        let options = self.configuration(&uri, false).await;
+                        if let Err(why) = self.update_document(&document, &options).await {
+                            warn!("Unable to reload document ({:?}): {}", why, uri.as_str());
+                        }
+                    }
+                }
+                FileChangeType::Deleted => {
+                    if workspace.find(&uri).is_none() || self.workspace_manager.is_open(&uri) {
+                        continue;
+                    }
+
+                    if let Ok(path) = uri.to_file_path() {
+                        self.workspace_manager.unload(|other| other == path);
+                        let params = PublishDiagnosticsParams {
+                            uri: uri.clone().into(),
+                            diagnostics: Vec::new(),
+                            version: None,
+                        };
+                        self.client.publish_diagnostics(params).await;
+                        documents_deleted = true;
+                    }
+                }
+            }
+        }
+
+        self.detect_children().await;
+        if documents_deleted {
+            self.action_manager.push(Action::PublishDiagnostics);
+        }
+
+        if log_changed {
+            self.update_build_diagnostics().await;
+        }
+
+        if config_changed {
+            self.config_file_manager.clear();
+            self.action_manager.push(Action::LoadConfiguration);
+        }
+    }
+
+    fn watch_workspace(&self) {
+        if !self.file_watcher.is_started() {
+            return;
+        }
+
+        let workspace = self.workspace_manager.get();
+        for document in workspace.documents() {
+            if let Ok(path) = document.uri.to_file_path() {
+                if let Some(directory) = path.parent() {
+                    self.file_watcher.watch(directory);
+                }
+            }
+        }
+
+        for folder in self.folder_manager.get() {
+            self.file_watcher.watch(&folder);
+        }
+    }
+
     async fn update_build_diagnostics(&self) {
         let workspace = self.workspace_manager.get();
         let mut diagnostics_manager = self.diagnostics_manager.lock().await;
-        let options = self.configuration(false).await;
 
-        for document in &workspace.documents {
+        for document in workspace.documents() {
             if document.uri.scheme() != "file" {
                 continue;
             }
 
             if let SyntaxTree::Latex(tree) = &document.tree {
                 if tree.env.is_standalone {
+                    let options = self.configuration(&document.uri, false).await;
                     match diagnostics_manager.build.update(&document.uri, &options) {
                         Ok(true) => self.action_manager.push(Action::PublishDiagnostics),
                         Ok(false) => (),
@@ -566,19 +1123,23 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
     async fn detect_root(&self, uri: Uri) {
         if uri.scheme() == "file" {
             let mut path = uri.to_file_path().unwrap();
-            let options = self.configuration(false).await;
+            let options = self.configuration(&uri, true).await;
+            // Without a workspace folder, the search continues up to the file system root.
+            let ceiling = self.folder_manager.find(&path);
+
             while path.pop() {
                 let workspace = self.workspace_manager.get();
                 if workspace.find_parent(&uri, &options).is_some() {
                     break;
                 }
 
-                for entry in WalkDir::new(&path)
+                let paths = WalkDir::new(&path)
                     .min_depth(1)
                     .max_depth(1)
                     .into_iter()
                     .filter_map(std::result::Result::ok)
                     .filter(|entry| entry.file_type().is_file())
+                    .filter(|entry| !options.is_excluded(entry.path()))
                     .filter(|entry| {
                         entry
                             .path()
@@ -587,12 +1148,17 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
                             .and_then(Language::by_extension)
                             .is_some()
                     })
-                {
-                    if let Ok(parent_uri) = Uri::from_file_path(entry.path()) {
-                        if workspace.find(&parent_uri).is_none() {
-                            let _ = self.workspace_manager.load(entry.path(), &options);
-                        }
-                    }
+                    .filter(|entry| {
+                        Uri::from_file_path(entry.path())
+                            .map_or(false, |parent_uri| workspace.find(&parent_uri).is_none())
+                    })
+                    .map(|entry| entry.into_path())
+                    .collect();
+
+                self.index_files(paths, &options).await;
+
+                if ceiling.as_ref() == Some(&path) {
+                    break;
                 }
             }
         }
@@ -602,18 +1168,15 @@ impl<C: LspClient + Send + Sync + 'static> LatexLspServer<C> {
 impl<C: LspClient + Send + Sync + 'static> Middleware for LatexLspServer<C> {
     #[boxed]
     async fn before_message(&self) {
-        self.detect_children().await;
-
-        let options = self.configuration(false).await;
-        let workspace = self.workspace_manager.get();
-        for document in &workspace.documents {
-            let _ = self.update_document(document, &options);
+        let events = self.file_watcher.take();
+        if !events.is_empty() {
+            self.update_files(events).await;
         }
     }
 
     #[boxed]
     async fn after_message(&self) {
-        self.update_build_diagnostics().await;
+        self.watch_workspace();
         for action in self.action_manager.take() {
             match action {
                 Action::RegisterCapabilities => {
@@ -634,6 +1197,35 @@ impl<C: LspClient + Send + Sync + 'static> Middleware for LatexLspServer<C> {
                             .await
                             .expect("failed to register \"workspace/didChangeConfiguration\"");
                     }
+
+                    if capabilities.has_watched_files_support() {
+                        let options = DidChangeWatchedFilesRegistrationOptions {
+                            watchers: vec![
+                                FileSystemWatcher {
+                                    glob_pattern: WATCHED_FILES_GLOB.into(),
+                                    kind: None,
+                                },
+                                FileSystemWatcher {
+                                    glob_pattern: format!("**/{{{}}}", CONFIG_FILE_NAMES.join(",")),
+                                    kind: None,
+                                },
+                            ],
+                        };
+                        let registration = Registration {
+                            id: "watched-files".into(),
+                            method: "workspace/didChangeWatchedFiles".into(),
+                            register_options: Some(serde_json::to_value(options).unwrap()),
+                        };
+                        let params = RegistrationParams {
+                            registrations: vec![registration],
+                        };
+                        self.client
+                            .register_capability(params)
+                            .await
+                            .expect("failed to register \"workspace/didChangeWatchedFiles\"");
+                    } else if let Err(why) = self.file_watcher.start() {
+                        warn!("Unable to start the file watcher: {}", why);
+                    }
                 }
                 Action::LoadDistribution => {
                     info!("Detected TeX distribution: {:?}", self.distribution.kind());
@@ -647,7 +1239,10 @@ impl<C: LspClient + Send + Sync + 'static> Middleware for LatexLspServer<C> {
                         self.client.show_message(params).await;
                     }
 
-                    if let Err(why) = self.distribution.load().await {
+                    let result = self
+                        .report_progress("Loading TeX distribution", self.distribution.load())
+                        .await;
+                    if let Err(why) = result {
                         let message = match why {
                             texlab_distro::LoadError::KpsewhichNotFound => {
                                 "An error occurred while executing `kpsewhich`.\
@@ -664,29 +1259,65 @@ impl<C: LspClient + Send + Sync + 'static> Middleware for LatexLspServer<C> {
                             typ: MessageType::Error,
                         };
                         self.client.show_message(params).await;
-                    };
+                    } else {
+                        let resolver = self.distribution.resolver().await;
+                        self.cache
+                            .store_resolver(self.distribution.kind(), &resolver);
+                    }
+                }
+                Action::RestoreCache => {
+                    if let Some(resolver) = self.cache.resolver(self.distribution.kind()) {
+                        self.distribution.restore(Arc::new(resolver)).await;
+                    }
+
+                    for folder in self.folder_manager.get() {
+                        let scope = Uri::from_file_path(&folder).ok();
+                        let options = self.scope_configuration(scope, false).await;
+                        self.workspace_manager.restore(&folder, &options).await;
+                    }
                 }
                 Action::LoadConfiguration => {
-                    let options = self.configuration(true).await;
+                    self.scope_configuration(None, true).await;
+                    for folder in self.folder_manager.get() {
+                        let scope = Uri::from_file_path(folder).ok();
+                        self.scope_configuration(scope, true).await;
+                    }
+
                     let workspace = self.workspace_manager.get();
-                    for document in &workspace.documents {
-                        if let Ok(path) = document.uri.to_file_path() {
-                            let _ = self.workspace_manager.load(&path, &options);
+                    for document in workspace.documents() {
+                        let options = self.configuration(&document.uri, false).await;
+                        if self.workspace_manager.is_open(&document.uri) {
+                            // The document might have been closed in the meantime.
+                            let _ = self
+                                .workspace_manager
+                                .reparse(&document.uri, &options)
+                                .await;
+                        } else if let Ok(path) = document.uri.to_file_path() {
+                            if let Err(WorkspaceLoadError::IO(_)) =
+                                self.workspace_manager.load(&path, &options).await
+                            {
+                                self.workspace_manager.unload(|other| other == path);
+                            }
                         }
                     }
                 }
+                Action::LoadFolder(folder) => {
+                    self.load_folder(&folder).await;
+                }
                 Action::UpdateConfiguration(settings) => {
                     self.config_strategy.get().unwrap().set(settings).await;
                 }
                 Action::DetectRoot(uri) => {
                     self.detect_root(uri).await;
+                    self.detect_children().await;
                 }
                 Action::PublishDiagnostics => {
                     let workspace = self.workspace_manager.get();
-                    for document in &workspace.documents {
+                    for document in workspace.documents() {
+                        let options = self.configuration(&document.uri, false).await;
                         let diagnostics = {
                             let manager = self.diagnostics_manager.lock().await;
-                            manager.get(&document)
+                            manager.get(&document, &options)
                         };
 
                         let params = PublishDiagnosticsParams {
@@ -699,7 +1330,7 @@ impl<C: LspClient + Send + Sync + 'static> Middleware for LatexLspServer<C> {
                 }
                 Action::RunLinter(uri, reason) => {
                     let options = self
-                        .configuration(true)
+                        .configuration(&uri, true)
                         .await
                         .latex
                         .and_then(|opts| opts.lint)
@@ -721,7 +1352,7 @@ impl<C: LspClient + Send + Sync + 'static> Middleware for LatexLspServer<C> {
                 }
                 Action::Build(uri) => {
                     let options = self
-                        .configuration(true)
+                        .configuration(&uri, true)
                         .await
                         .latex
                         .and_then(|opts| opts.build)
@@ -737,5 +1368,10 @@ impl<C: LspClient + Send + Sync + 'static> Middleware for LatexLspServer<C> {
                 }
             }
         }
+
+        if self.cache.is_save_due() {
+            let cache = Arc::clone(&self.cache);
+            task::spawn_blocking(move || cache.save());
+        }
     }
 }
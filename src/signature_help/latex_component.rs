@@ -0,0 +1,118 @@
+use super::{find_argument, make_signature_help};
+use futures_boxed::boxed;
+use texlab_protocol::{Documentation, SignatureHelp, TextDocumentPositionParams};
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LatexComponentSignatureHelpProvider;
+
+impl FeatureProvider for LatexComponentSignatureHelpProvider {
+    type Params = TextDocumentPositionParams;
+    type Output = Option<SignatureHelp>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<TextDocumentPositionParams>,
+    ) -> Option<SignatureHelp> {
+        if let SyntaxTree::Latex(tree) = &request.document().tree {
+            let (command, kind, index) = find_argument(tree, request.params.position)?;
+            if kind != LatexGroupKind::Group {
+                return None;
+            }
+
+            let name = &command.name.text()[1..];
+            for component in COMPONENT_DATABASE.related_components(request.related_documents()) {
+                let definition = component.commands.iter().find(|definition| {
+                    definition.name == name && !definition.parameters.is_empty()
+                });
+
+                if let Some(definition) = definition {
+                    if index >= definition.parameters.len() {
+                        return None;
+                    }
+
+                    let parameters = definition
+                        .parameters
+                        .iter()
+                        .map(|parameter| {
+                            if parameter.0.is_empty() {
+                                None
+                            } else {
+                                let arguments: Vec<_> = parameter
+                                    .0
+                                    .iter()
+                                    .map(|argument| argument.name.as_str())
+                                    .collect();
+                                Some(Documentation::String(arguments.join(", ")))
+                            }
+                        })
+                        .collect();
+
+                    return Some(make_signature_help(
+                        command.name.text(),
+                        parameters,
+                        None,
+                        false,
+                        index,
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::Position;
+
+    #[test]
+    fn mathbb() {
+        let help = test_feature(
+            LatexComponentSignatureHelpProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.tex",
+                    "\\usepackage{amsfonts}\n\\mathbb{}",
+                )],
+                main_file: "foo.tex",
+                position: Position::new(1, 8),
+                ..FeatureSpec::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(help.active_parameter, Some(0));
+        assert_eq!(help.signatures[0].label, "\\mathbb{#1}");
+    }
+
+    #[test]
+    fn unknown_command() {
+        let help = test_feature(
+            LatexComponentSignatureHelpProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.tex", "\\foo{a}")],
+                main_file: "foo.tex",
+                position: Position::new(0, 5),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(help, None);
+    }
+
+    #[test]
+    fn bibtex() {
+        let help = test_feature(
+            LatexComponentSignatureHelpProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file("foo.bib", "@article{foo,}")],
+                main_file: "foo.bib",
+                position: Position::new(0, 10),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(help, None);
+    }
+}
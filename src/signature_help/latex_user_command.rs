@@ -0,0 +1,155 @@
+use super::{find_argument, make_signature_help};
+use futures_boxed::boxed;
+use texlab_protocol::{Documentation, SignatureHelp, TextDocumentPositionParams};
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LatexUserCommandSignatureHelpProvider;
+
+impl FeatureProvider for LatexUserCommandSignatureHelpProvider {
+    type Params = TextDocumentPositionParams;
+    type Output = Option<SignatureHelp>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<TextDocumentPositionParams>,
+    ) -> Option<SignatureHelp> {
+        if let SyntaxTree::Latex(tree) = &request.document().tree {
+            let (command, kind, index) = find_argument(tree, request.params.position)?;
+            for document in request.related_documents() {
+                if let SyntaxTree::Latex(tree) = &document.tree {
+                    let definition = tree.command_definitions.iter().find(|definition| {
+                        definition.definition.name.text() == command.name.text()
+                    });
+
+                    if let Some(definition) = definition {
+                        let count = definition.argument_count();
+                        let has_optional_argument = definition.has_optional_argument();
+                        let index = match kind {
+                            LatexGroupKind::Options if has_optional_argument && index == 0 => 0,
+                            LatexGroupKind::Options => return None,
+                            LatexGroupKind::Group if has_optional_argument => index + 1,
+                            LatexGroupKind::Group => index,
+                        };
+
+                        if index >= count {
+                            return None;
+                        }
+
+                        let implementation = extract_group(&definition.implementation);
+                        return Some(make_signature_help(
+                            command.name.text(),
+                            vec![None; count],
+                            Some(Documentation::String(implementation)),
+                            has_optional_argument,
+                            index,
+                        ));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use texlab_protocol::{ParameterLabel, Position};
+
+    #[test]
+    fn second_argument() {
+        let help = test_feature(
+            LatexUserCommandSignatureHelpProvider,
+            FeatureSpec {
+                files: vec![
+                    FeatureSpec::file("foo.tex", "\\include{bar}\n\\foo{a}{b}"),
+                    FeatureSpec::file("bar.tex", "\\newcommand{\\foo}[2]{#1 and #2}"),
+                ],
+                main_file: "foo.tex",
+                position: Position::new(1, 8),
+                ..FeatureSpec::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(help.active_parameter, Some(1));
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "\\foo{#1}{#2}");
+        let parameters = signature.parameters.as_ref().unwrap();
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters[1].label, ParameterLabel::Simple("#2".into()));
+    }
+
+    #[test]
+    fn optional_argument() {
+        let help = test_feature(
+            LatexUserCommandSignatureHelpProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.tex",
+                    "\\newcommand{\\foo}[2][x]{#1 and #2}\n\\foo[a]{b}",
+                )],
+                main_file: "foo.tex",
+                position: Position::new(1, 9),
+                ..FeatureSpec::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(help.active_parameter, Some(1));
+        assert_eq!(help.signatures[0].label, "\\foo[#1]{#2}");
+    }
+
+    #[test]
+    fn optional_argument_active() {
+        let help = test_feature(
+            LatexUserCommandSignatureHelpProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.tex",
+                    "\\newcommand{\\foo}[2][x]{#1 and #2}\n\\foo[a]{b}",
+                )],
+                main_file: "foo.tex",
+                position: Position::new(1, 6),
+                ..FeatureSpec::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(help.active_parameter, Some(0));
+    }
+
+    #[test]
+    fn too_many_arguments() {
+        let help = test_feature(
+            LatexUserCommandSignatureHelpProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.tex",
+                    "\\newcommand{\\foo}[1]{#1}\n\\foo{a}{b}",
+                )],
+                main_file: "foo.tex",
+                position: Position::new(1, 8),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(help, None);
+    }
+
+    #[test]
+    fn outside_argument() {
+        let help = test_feature(
+            LatexUserCommandSignatureHelpProvider,
+            FeatureSpec {
+                files: vec![FeatureSpec::file(
+                    "foo.tex",
+                    "\\newcommand{\\foo}[1]{#1}\n\\foo{a}",
+                )],
+                main_file: "foo.tex",
+                position: Position::new(1, 2),
+                ..FeatureSpec::default()
+            },
+        );
+        assert_eq!(help, None);
+    }
+}
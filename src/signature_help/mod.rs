@@ -0,0 +1,105 @@
+mod latex_component;
+mod latex_user_command;
+
+use self::latex_component::LatexComponentSignatureHelpProvider;
+use self::latex_user_command::LatexUserCommandSignatureHelpProvider;
+use futures_boxed::boxed;
+use std::sync::Arc;
+use texlab_protocol::*;
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+pub struct SignatureHelpProvider {
+    provider: ChoiceProvider<TextDocumentPositionParams, SignatureHelp>,
+}
+
+impl SignatureHelpProvider {
+    pub fn new() -> Self {
+        Self {
+            provider: ChoiceProvider::new(vec![
+                Box::new(LatexUserCommandSignatureHelpProvider),
+                Box::new(LatexComponentSignatureHelpProvider),
+            ]),
+        }
+    }
+}
+
+impl Default for SignatureHelpProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeatureProvider for SignatureHelpProvider {
+    type Params = TextDocumentPositionParams;
+    type Output = Option<SignatureHelp>;
+
+    #[boxed]
+    async fn execute<'a>(
+        &'a self,
+        request: &'a FeatureRequest<TextDocumentPositionParams>,
+    ) -> Option<SignatureHelp> {
+        self.provider.execute(request).await
+    }
+}
+
+/// Finds the innermost command argument that contains the position.
+/// The index counts only the arguments of the same kind.
+fn find_argument(
+    tree: &LatexSyntaxTree,
+    position: Position,
+) -> Option<(Arc<LatexCommand>, LatexGroupKind, usize)> {
+    tree.find(position)
+        .into_iter()
+        .rev()
+        .filter_map(|node| match node {
+            LatexNode::Command(command) => Some(command),
+            _ => None,
+        })
+        .find_map(|command| {
+            let group_index = command.groups.iter().position(|group| {
+                group.range.contains(position)
+                    && position != group.start()
+                    && (group.right.is_none() || position != group.end())
+            })?;
+            let kind = command.groups[group_index].kind;
+            let index = command.groups[..group_index]
+                .iter()
+                .filter(|group| group.kind == kind)
+                .count();
+            Some((command, kind, index))
+        })
+}
+
+fn make_signature_help(
+    name: &str,
+    parameters: Vec<Option<Documentation>>,
+    documentation: Option<Documentation>,
+    has_optional_parameter: bool,
+    active_parameter: usize,
+) -> SignatureHelp {
+    let mut label = name.to_owned();
+    let mut infos = Vec::new();
+    for (i, documentation) in parameters.into_iter().enumerate() {
+        let parameter = format!("#{}", i + 1);
+        if i == 0 && has_optional_parameter {
+            label.push_str(&format!("[{}]", parameter));
+        } else {
+            label.push_str(&format!("{{{}}}", parameter));
+        }
+        infos.push(ParameterInformation {
+            label: ParameterLabel::Simple(parameter),
+            documentation,
+        });
+    }
+
+    SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation,
+            parameters: Some(infos),
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter as i64),
+    }
+}
@@ -0,0 +1,188 @@
+use futures_boxed::boxed;
+use texlab_protocol::*;
+use texlab_syntax::*;
+use texlab_workspace::*;
+
+const TODO_COMMAND: &str = "\\todo";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct TodoProvider;
+
+impl FeatureProvider for TodoProvider {
+    type Params = TodoParams;
+    type Output = Vec<Todo>;
+
+    #[boxed]
+    async fn execute<'a>(&'a self, request: &'a FeatureRequest<TodoParams>) -> Vec<Todo> {
+        request
+            .related_documents()
+            .iter()
+            .flat_map(|document| find_todos(document, &request.options))
+            .collect()
+    }
+}
+
+pub fn find_todos(document: &Document, options: &Options) -> Vec<Todo> {
+    let tree = match &document.tree {
+        SyntaxTree::Latex(tree) => tree,
+        SyntaxTree::Bibtex(_) => return Vec::new(),
+    };
+
+    let tags = options
+        .latex
+        .as_ref()
+        .and_then(|latex| latex.todo.clone())
+        .unwrap_or_default()
+        .tags();
+
+    let mut todos = Vec::new();
+    for comment in &tree.comments {
+        if let Some((tag, message)) = parse_comment(comment.text(), &tags) {
+            todos.push(Todo {
+                location: Location::new(document.uri.clone().into(), comment.range()),
+                tag: tag.to_owned(),
+                message: message.to_owned(),
+            });
+        }
+    }
+
+    for command in &tree.commands {
+        if command.name.text() == TODO_COMMAND {
+            let message = command
+                .args
+                .get(0)
+                .map(|arg| extract_group(arg))
+                .unwrap_or_default();
+
+            todos.push(Todo {
+                location: Location::new(document.uri.clone().into(), command.range),
+                tag: "TODO".to_owned(),
+                message,
+            });
+        }
+    }
+
+    todos.sort_by_key(|todo| todo.location.range.start);
+    todos
+}
+
+fn parse_comment<'a>(text: &'a str, tags: &'a [String]) -> Option<(&'a str, &'a str)> {
+    let text = text.trim_start_matches('%').trim_start();
+    tags.iter().find_map(|tag| {
+        if !text.starts_with(tag.as_str()) {
+            return None;
+        }
+
+        let rest = &text[tag.len()..];
+        match rest.chars().next() {
+            None => Some((tag.as_str(), "")),
+            Some(c) if c == ':' || c.is_whitespace() => {
+                let message = rest.trim_start_matches(':').trim();
+                Some((tag.as_str(), message))
+            }
+            Some(_) => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn verify(spec: FeatureSpec) -> Vec<(String, String, Range)> {
+        test_feature(TodoProvider, spec)
+            .into_iter()
+            .map(|todo| (todo.tag, todo.message, todo.location.range))
+            .collect()
+    }
+
+    #[test]
+    fn comment() {
+        let todos = verify(FeatureSpec {
+            files: vec![FeatureSpec::file(
+                "foo.tex",
+                indoc!(
+                    r#"
+                        % TODO: foo
+                        bar % FIXME baz
+                        % TODOS"#
+                ),
+            )],
+            main_file: "foo.tex",
+            ..FeatureSpec::default()
+        });
+        assert_eq!(
+            todos,
+            vec![
+                ("TODO".into(), "foo".into(), Range::new_simple(0, 0, 0, 11)),
+                ("FIXME".into(), "baz".into(), Range::new_simple(1, 4, 1, 15)),
+            ]
+        );
+    }
+
+    #[test]
+    fn command() {
+        let todos = verify(FeatureSpec {
+            files: vec![FeatureSpec::file("foo.tex", "\\todo[inline]{foo bar}")],
+            main_file: "foo.tex",
+            ..FeatureSpec::default()
+        });
+        assert_eq!(
+            todos,
+            vec![(
+                "TODO".into(),
+                "foo bar".into(),
+                Range::new_simple(0, 0, 0, 22)
+            )]
+        );
+    }
+
+    #[test]
+    fn custom_tags() {
+        let todos = verify(FeatureSpec {
+            files: vec![FeatureSpec::file("foo.tex", "% TODO: foo\n% XXX: bar")],
+            main_file: "foo.tex",
+            options: Options {
+                latex: Some(LatexOptions {
+                    todo: Some(LatexTodoOptions {
+                        tags: Some(vec!["XXX".into()]),
+                    }),
+                    ..LatexOptions::default()
+                }),
+                bibtex: None,
+            },
+            ..FeatureSpec::default()
+        });
+        assert_eq!(
+            todos,
+            vec![("XXX".into(), "bar".into(), Range::new_simple(1, 0, 1, 10))]
+        );
+    }
+
+    #[test]
+    fn related_documents() {
+        let todos = verify(FeatureSpec {
+            files: vec![
+                FeatureSpec::file("foo.tex", "\\include{bar}"),
+                FeatureSpec::file("bar.tex", "% TODO"),
+            ],
+            main_file: "foo.tex",
+            ..FeatureSpec::default()
+        });
+        assert_eq!(
+            todos,
+            vec![("TODO".into(), "".into(), Range::new_simple(0, 0, 0, 6))]
+        );
+    }
+
+    #[test]
+    fn bibtex() {
+        let todos = verify(FeatureSpec {
+            files: vec![FeatureSpec::file("foo.bib", "% TODO: foo")],
+            main_file: "foo.bib",
+            ..FeatureSpec::default()
+        });
+        assert!(todos.is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::config_file;
+use log::warn;
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use texlab_protocol::{FileChangeType, FileEvent, Uri};
+
+pub const WATCHED_FILES_GLOB: &str = "**/*.{tex,bib,aux,log,sty,cls}";
+
+const WATCHED_EXTENSIONS: &[&str] = &["tex", "bib", "aux", "log", "sty", "cls"];
+
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(200);
+
+struct WatcherState {
+    watcher: RecommendedWatcher,
+    directories: HashSet<PathBuf>,
+}
+
+/// Fallback for clients that cannot send `workspace/didChangeWatchedFiles`.
+/// The collected events are handed out by `take` before the next message is processed.
+#[derive(Default)]
+pub struct FileWatcher {
+    events: Arc<Mutex<Vec<FileEvent>>>,
+    state: Mutex<Option<WatcherState>>,
+}
+
+impl FileWatcher {
+    pub fn start(&self) -> notify::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.is_some() {
+            return Ok(());
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let watcher = notify::watcher(tx, DEBOUNCE_DELAY)?;
+        let events = Arc::clone(&self.events);
+        thread::spawn(move || {
+            for event in rx {
+                if let Some(event) = convert_event(event) {
+                    events.lock().unwrap().push(event);
+                }
+            }
+        });
+
+        *state = Some(WatcherState {
+            watcher,
+            directories: HashSet::new(),
+        });
+        Ok(())
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.state.lock().unwrap().is_some()
+    }
+
+    pub fn watch(&self, directory: &Path) {
+        let mut guard = self.state.lock().unwrap();
+        if let Some(state) = guard.as_mut() {
+            if state.directories.contains(directory) {
+                return;
+            }
+
+            match state.watcher.watch(directory, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    state.directories.insert(directory.to_owned());
+                }
+                Err(why) => warn!("Unable to watch {}: {}", directory.display(), why),
+            }
+        }
+    }
+
+    pub fn take(&self) -> Vec<FileEvent> {
+        let mut events = self.events.lock().unwrap();
+        mem::replace(&mut *events, Vec::new())
+    }
+}
+
+fn convert_event(event: DebouncedEvent) -> Option<FileEvent> {
+    let (path, typ) = match event {
+        DebouncedEvent::Create(path) => (path, FileChangeType::Created),
+        DebouncedEvent::Write(path) => (path, FileChangeType::Changed),
+        DebouncedEvent::Remove(path) => (path, FileChangeType::Deleted),
+        DebouncedEvent::Rename(_, path) => (path, FileChangeType::Created),
+        _ => return None,
+    };
+
+    let is_watched = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map_or(false, |extension| WATCHED_EXTENSIONS.contains(&extension));
+
+    if !is_watched && !config_file::is_config_file(&path) {
+        return None;
+    }
+
+    let uri = Uri::from_file_path(&path).ok()?;
+    Some(FileEvent::new(uri.into(), typ))
+}
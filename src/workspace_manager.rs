@@ -1,14 +1,17 @@
-use futures::executor::block_on;
+use crate::cache::DocumentCache;
+use futures::future;
 use log::*;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::Mutex;
-use texlab_distro::{Distribution, Language};
-use texlab_protocol::{Options, TextDocumentItem, Uri};
-use texlab_syntax::SyntaxTree;
+use texlab_distro::{Distribution, Language, Resolver};
+use texlab_protocol::{Options, TextDocumentContentChangeEvent, TextDocumentItem, Uri};
+use texlab_syntax::edit_text;
 use texlab_workspace::{Document, Workspace};
+use tokio::task;
 
 #[derive(Debug)]
 pub enum WorkspaceLoadError {
@@ -17,16 +20,27 @@ pub enum WorkspaceLoadError {
     IO(std::io::Error),
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WorkspaceUpdateError {
+    DocumentNotFound,
+}
+
+/// Parsing happens on the blocking thread pool.
+/// The lock is only held to swap in the new workspace.
 pub struct WorkspaceManager {
     distribution: Arc<Box<dyn Distribution>>,
+    cache: Arc<DocumentCache>,
     workspace: Mutex<Arc<Workspace>>,
+    open_documents: Mutex<HashSet<Uri>>,
 }
 
 impl WorkspaceManager {
-    pub fn new(distribution: Arc<Box<dyn Distribution>>) -> Self {
+    pub fn new(distribution: Arc<Box<dyn Distribution>>, cache: Arc<DocumentCache>) -> Self {
         Self {
             distribution,
+            cache,
             workspace: Mutex::default(),
+            open_documents: Mutex::default(),
         }
     }
 
@@ -35,7 +49,7 @@ impl WorkspaceManager {
         Arc::clone(&workspace)
     }
 
-    pub fn add(&self, document: TextDocumentItem, options: &Options) {
+    pub async fn add(&self, document: TextDocumentItem, options: &Options) {
         let language = match Language::by_language_id(&document.language_id) {
             Some(language) => language,
             None => {
@@ -44,17 +58,219 @@ impl WorkspaceManager {
             }
         };
 
+        let uri: Uri = document.uri.into();
+        self.open_documents.lock().unwrap().insert(uri.clone());
+        let text = document.text;
+        let options = options.clone();
+        let resolver = self.distribution.resolver().await;
+        let document =
+            task::spawn_blocking(move || Document::parse(uri, text, language, &options, &resolver))
+                .await
+                .unwrap();
+
+        self.modify(|workspace| workspace.insert(Arc::new(document)));
+    }
+
+    pub async fn load(&self, path: &Path, options: &Options) -> Result<(), WorkspaceLoadError> {
+        self.load_all(vec![path.to_owned()], options)
+            .await
+            .pop()
+            .unwrap()
+    }
+
+    /// Loads the given files in parallel and adds them to the workspace at once.
+    /// Documents that are open in the editor are not replaced.
+    pub async fn load_all(
+        &self,
+        paths: Vec<PathBuf>,
+        options: &Options,
+    ) -> Vec<Result<(), WorkspaceLoadError>> {
+        let resolver = self.distribution.resolver().await;
+        let tasks = paths.into_iter().map(|path| {
+            let options = options.clone();
+            let resolver = Arc::clone(&resolver);
+            let cache = Arc::clone(&self.cache);
+            task::spawn_blocking(move || Self::read(&path, &options, &resolver, &cache))
+        });
+
+        let mut documents = Vec::new();
+        let mut results = Vec::new();
+        for result in future::join_all(tasks).await {
+            match result.unwrap() {
+                Ok(document) => {
+                    documents.push(Arc::new(document));
+                    results.push(Ok(()));
+                }
+                Err(why) => results.push(Err(why)),
+            }
+        }
+
+        if !documents.is_empty() {
+            self.modify(|workspace| {
+                for document in documents {
+                    if !self.is_open(&document.uri) {
+                        workspace.insert(document);
+                    }
+                }
+            });
+        }
+        results
+    }
+
+    /// Adds the cached documents inside the given directory without reading them from disk.
+    /// They are revalidated once they are loaded again.
+    pub async fn restore(&self, directory: &Path, options: &Options) {
+        let directory = directory.to_owned();
+        let cache = Arc::clone(&self.cache);
+        let options = options.clone();
+        let resolver = self.distribution.resolver().await;
+        let documents = task::spawn_blocking(move || {
+            cache
+                .entries_in(&directory)
+                .into_iter()
+                .filter_map(|(path, entry)| {
+                    let uri = Uri::from_file_path(&path).ok()?;
+                    let document = Document::restore(
+                        uri,
+                        entry.text,
+                        entry.tree,
+                        entry.modified,
+                        &options,
+                        &resolver,
+                    );
+                    Some(Arc::new(document))
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap();
+
+        self.modify(|workspace| {
+            for document in documents {
+                if workspace.find(&document.uri).is_none() {
+                    workspace.insert(document);
+                }
+            }
+        });
+    }
+
+    /// Applies the changes of the editor to the document.
+    pub async fn update(
+        &self,
+        uri: &Uri,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        options: &Options,
+    ) -> Result<(), WorkspaceUpdateError> {
+        self.replace(uri, options, move |old_document, options, resolver| {
+            Self::edit(old_document, changes.clone(), options, resolver)
+        })
+        .await
+    }
+
+    /// Parses the current text of the document again, e.g. after the options have changed.
+    pub async fn reparse(&self, uri: &Uri, options: &Options) -> Result<(), WorkspaceUpdateError> {
+        self.replace(uri, options, |old_document, options, resolver| {
+            let uri = old_document.uri.clone();
+            let text = old_document.text.clone();
+            let language = old_document.tree.language();
+            Document::parse(uri, text, language, options, resolver)
+        })
+        .await
+    }
+
+    /// Replaces the document with the result of `action`.
+    /// If the document has been replaced while `action` was running,
+    /// `action` is applied again to the new document so that no change gets lost.
+    async fn replace<F>(
+        &self,
+        uri: &Uri,
+        options: &Options,
+        action: F,
+    ) -> Result<(), WorkspaceUpdateError>
+    where
+        F: Fn(&Document, &Options, &Resolver) -> Document + Clone + Send + 'static,
+    {
+        let resolver = self.distribution.resolver().await;
+        loop {
+            let old_document = self
+                .get()
+                .find(uri)
+                .ok_or(WorkspaceUpdateError::DocumentNotFound)?;
+
+            let document = {
+                let old_document = Arc::clone(&old_document);
+                let options = options.clone();
+                let resolver = Arc::clone(&resolver);
+                let action = action.clone();
+                task::spawn_blocking(move || action(&old_document, &options, &resolver))
+                    .await
+                    .unwrap()
+            };
+
+            {
+                let mut workspace = self.workspace.lock().unwrap();
+                let is_current = workspace
+                    .find(uri)
+                    .map_or(false, |document| Arc::ptr_eq(&document, &old_document));
+
+                if is_current {
+                    let mut new_workspace = Workspace::clone(&workspace);
+                    new_workspace.insert(Arc::new(document));
+                    *workspace = Arc::new(new_workspace);
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    pub fn close(&self, uri: &Uri) {
+        self.open_documents.lock().unwrap().remove(uri);
+    }
+
+    /// Checks whether the document is open in the editor and thus not owned by the file system.
+    pub fn is_open(&self, uri: &Uri) -> bool {
+        self.open_documents.lock().unwrap().contains(uri)
+    }
+
+    /// Removes the matching documents from the workspace.
+    /// Documents that are open in the editor are kept.
+    pub fn unload<F>(&self, predicate: F)
+    where
+        F: Fn(&Path) -> bool,
+    {
+        let open_documents = self.open_documents.lock().unwrap().clone();
+        let mut workspace = self.workspace.lock().unwrap();
+        let mut new_workspace = Workspace::clone(&workspace);
+        for document in workspace.documents() {
+            if open_documents.contains(&document.uri) {
+                continue;
+            }
+
+            if let Ok(path) = document.uri.to_file_path() {
+                if predicate(&path) {
+                    new_workspace.remove(&document.uri);
+                }
+            }
+        }
+        *workspace = Arc::new(new_workspace);
+    }
+
+    fn modify<F>(&self, action: F)
+    where
+        F: FnOnce(&mut Workspace),
+    {
         let mut workspace = self.workspace.lock().unwrap();
-        *workspace = self.add_or_update(
-            &workspace,
-            document.uri.into(),
-            document.text,
-            language,
-            options,
-        );
+        let mut new_workspace = Workspace::clone(&workspace);
+        action(&mut new_workspace);
+        *workspace = Arc::new(new_workspace);
     }
 
-    pub fn load(&self, path: &Path, options: &Options) -> Result<(), WorkspaceLoadError> {
+    fn read(
+        path: &Path,
+        options: &Options,
+        resolver: &Resolver,
+        cache: &DocumentCache,
+    ) -> Result<Document, WorkspaceLoadError> {
         let language = match path
             .extension()
             .and_then(OsStr::to_str)
@@ -75,6 +291,20 @@ impl WorkspaceManager {
             }
         };
 
+        if let Some(entry) = cache.get(path) {
+            if entry.tree.language() == language {
+                return Ok(Document::restore(
+                    uri,
+                    entry.text,
+                    entry.tree,
+                    entry.modified,
+                    options,
+                    resolver,
+                ));
+            }
+        }
+
+        let modified = fs::metadata(path).and_then(|data| data.modified());
         let text = match fs::read_to_string(path) {
             Ok(text) => text,
             Err(why) => {
@@ -83,48 +313,39 @@ impl WorkspaceManager {
             }
         };
 
-        let mut workspace = self.workspace.lock().unwrap();
-        *workspace = self.add_or_update(&workspace, uri, text, language, options);
-        Ok(())
+        let document = Document::parse(uri, text, language, options, resolver);
+        if let Ok(modified) = modified {
+            let snapshot = document.tree.snapshot(options);
+            cache.insert(path.to_owned(), modified, document.text.clone(), snapshot);
+        }
+        Ok(document)
     }
 
-    pub fn update(&self, uri: Uri, text: String, options: &Options) {
-        let mut workspace = self.workspace.lock().unwrap();
+    fn edit(
+        old_document: &Document,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        options: &Options,
+        resolver: &Resolver,
+    ) -> Document {
+        match changes.as_slice() {
+            [TextDocumentContentChangeEvent {
+                range: Some(range),
+                text,
+                ..
+            }] => old_document.edit(*range, text, options, resolver),
+            _ => {
+                let mut text = old_document.text.clone();
+                for change in changes {
+                    match change.range {
+                        Some(range) => edit_text(&mut text, range, &change.text),
+                        None => text = change.text,
+                    }
+                }
 
-        let old_document = match workspace.documents.iter().find(|x| x.uri == uri) {
-            Some(document) => document,
-            None => {
-                warn!("Document not found: {}", uri);
-                return;
+                let uri = old_document.uri.clone();
+                let language = old_document.tree.language();
+                Document::parse(uri, text, language, options, resolver)
             }
-        };
-
-        let language = match old_document.tree {
-            SyntaxTree::Latex(_) => Language::Latex,
-            SyntaxTree::Bibtex(_) => Language::Bibtex,
-        };
-
-        *workspace = self.add_or_update(&workspace, uri, text, language, options);
-    }
-
-    fn add_or_update(
-        &self,
-        workspace: &Workspace,
-        uri: Uri,
-        text: String,
-        language: Language,
-        options: &Options,
-    ) -> Arc<Workspace> {
-        let resolver = block_on(self.distribution.resolver());
-        let document = Document::parse(uri, text, language, &options, &resolver);
-        let mut documents: Vec<Arc<Document>> = workspace
-            .documents
-            .iter()
-            .filter(|x| x.uri != document.uri)
-            .cloned()
-            .collect();
-
-        documents.push(Arc::new(document));
-        Arc::new(Workspace { documents })
+        }
     }
 }
@@ -0,0 +1,43 @@
+use texlab_protocol::*;
+use texlab_test::{Scenario, CLIENT_FULL_CAPABILITIES};
+
+#[tokio::test]
+async fn clean_auxiliary() {
+    let scenario = Scenario::new("clean", false).await;
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+    scenario.open("foo.tex").await;
+
+    let text_document = TextDocumentIdentifier::new(scenario.uri("foo.tex").into());
+    let params = ExecuteCommandParams {
+        command: "texlab.cleanAuxiliary".to_owned(),
+        arguments: vec![serde_json::to_value(text_document).unwrap()],
+        work_done_progress_params: WorkDoneProgressParams::default(),
+    };
+    scenario
+        .server
+        .execute(|svr| svr.execute_command(params))
+        .await
+        .unwrap();
+
+    let directory = scenario.directory.path();
+    assert!(directory.join("foo.tex").exists());
+    assert!(!directory.join("foo.aux").exists());
+    assert!(!directory.join("foo.log").exists());
+}
+
+#[tokio::test]
+async fn unknown_command() {
+    let scenario = Scenario::new("clean", false).await;
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+
+    let params = ExecuteCommandParams {
+        command: "texlab.foo".to_owned(),
+        arguments: Vec::new(),
+        work_done_progress_params: WorkDoneProgressParams::default(),
+    };
+    let result = scenario
+        .server
+        .execute(|svr| svr.execute_command(params))
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use texlab_protocol::*;
+use texlab_test::{Scenario, CLIENT_FULL_CAPABILITIES};
+use tokio::fs;
+
+async fn format(scenario: &Scenario) -> Vec<TextEdit> {
+    let params = DocumentFormattingParams {
+        text_document: TextDocumentIdentifier::new(scenario.uri("unformatted.bib").into()),
+        options: FormattingOptions {
+            tab_size: 4,
+            insert_spaces: true,
+            properties: HashMap::new(),
+        },
+    };
+    scenario
+        .server
+        .execute(|svr| svr.formatting(params))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn project_options() {
+    let scenario = Scenario::new("config_file", false).await;
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+    scenario.open("unformatted.bib").await;
+    let edits = format(&scenario).await;
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, scenario.read("formatted.bib").await);
+}
+
+#[tokio::test]
+async fn reload() {
+    let scenario = Scenario::new("config_file", false).await;
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+    scenario.open("unformatted.bib").await;
+    format(&scenario).await;
+
+    let config_path = scenario.uri("texlab.toml").to_file_path().unwrap();
+    fs::write(config_path, "").await.unwrap();
+    let params = DidChangeWatchedFilesParams {
+        changes: vec![FileEvent::new(
+            scenario.uri("texlab.toml").into(),
+            FileChangeType::Changed,
+        )],
+    };
+    scenario
+        .server
+        .execute(|svr| svr.did_change_watched_files(params))
+        .await;
+
+    let edits = format(&scenario).await;
+    assert_eq!(edits.len(), 1);
+    assert_ne!(edits[0].new_text, scenario.read("formatted.bib").await);
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use texlab_protocol::*;
+use texlab_test::{Scenario, CLIENT_FULL_CAPABILITIES};
+
+#[tokio::test]
+async fn document_scope() {
+    let scenario = Scenario::new("formatting/bibtex", false).await;
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+    let file = "infinite_line_length/unformatted.bib";
+    let options = Options {
+        latex: None,
+        bibtex: Some(BibtexOptions {
+            formatting: Some(BibtexFormattingOptions {
+                line_length: Some(0),
+            }),
+        }),
+    };
+    scenario
+        .client
+        .scoped_options
+        .lock()
+        .await
+        .insert(scenario.uri(file), options);
+    scenario.open(file).await;
+
+    let params = DocumentFormattingParams {
+        text_document: TextDocumentIdentifier::new(scenario.uri(file).into()),
+        options: FormattingOptions {
+            tab_size: 4,
+            insert_spaces: true,
+            properties: HashMap::new(),
+        },
+    };
+    let edits = scenario
+        .server
+        .execute(|svr| svr.formatting(params))
+        .await
+        .unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].new_text,
+        scenario.read("infinite_line_length/formatted.bib").await
+    );
+}
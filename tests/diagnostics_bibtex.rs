@@ -32,3 +32,31 @@ async fn did_change_update() {
         assert_eq!(diagnostics.len(), 0);
     }
 }
+
+#[tokio::test]
+async fn did_change_incremental() {
+    let scenario = Scenario::new("diagnostics/bibtex", false).await;
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+    scenario.open("foo.bib").await;
+    let params = DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier::new(scenario.uri("foo.bib").into(), 0),
+        content_changes: vec![
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new_simple(0, 8, 0, 8)),
+                range_length: Some(0),
+                text: "{foo}".into(),
+            },
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new_simple(0, 12, 0, 12)),
+                range_length: Some(0),
+                text: ",".into(),
+            },
+        ],
+    };
+    scenario.server.execute(|svr| svr.did_change(params)).await;
+    {
+        let diagnostics_by_uri = scenario.client.diagnostics_by_uri.lock().await;
+        let diagnostics = &diagnostics_by_uri[&scenario.uri("foo.bib")];
+        assert_eq!(diagnostics.len(), 0);
+    }
+}
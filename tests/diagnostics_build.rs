@@ -1,3 +1,4 @@
+use texlab_protocol::*;
 use texlab_test::{Scenario, CLIENT_FULL_CAPABILITIES};
 use tokio::fs;
 
@@ -14,7 +15,16 @@ async fn did_change_update() {
     }
     let log_path = scenario.uri("foo.log").to_file_path().unwrap();
     fs::write(log_path, "").await.unwrap();
-    scenario.server.execute(|_| async move { () }).await;
+    let params = DidChangeWatchedFilesParams {
+        changes: vec![FileEvent::new(
+            scenario.uri("foo.log").into(),
+            FileChangeType::Changed,
+        )],
+    };
+    scenario
+        .server
+        .execute(|svr| svr.did_change_watched_files(params))
+        .await;
     {
         let diagnostics_by_uri = scenario.client.diagnostics_by_uri.lock().await;
         let diagnostics = &diagnostics_by_uri[&scenario.uri("foo.tex")];
@@ -28,3 +28,15 @@ async fn infinite_line_length() {
     );
     assert_eq!(edits[0].range, Range::new_simple(0, 0, 0, 149));
 }
+
+#[tokio::test]
+async fn range() {
+    let (scenario, edits) =
+        run_bibtex_range("range/unformatted.bib", None, Range::new_simple(1, 0, 1, 5)).await;
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].new_text,
+        scenario.read("range/formatted.bib").await
+    );
+    assert_eq!(edits[0].range, Range::new_simple(1, 0, 1, 33));
+}
@@ -0,0 +1,13 @@
+use texlab_protocol::*;
+use texlab_test::formatting::*;
+
+#[tokio::test]
+async fn default_settings() {
+    let (scenario, edits) = run_latex("default/unformatted.tex", None).await;
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].new_text,
+        scenario.read("default/formatted.tex").await
+    );
+    assert_eq!(edits[0].range, Range::new_simple(0, 0, 6, 0));
+}
@@ -0,0 +1,28 @@
+use texlab_protocol::*;
+use texlab_test::{Scenario, CLIENT_FULL_CAPABILITIES};
+
+#[tokio::test]
+async fn detect_root_exclude() {
+    let scenario = Scenario::new("workspace/exclude", false).await;
+    *scenario.client.options.lock().await = Options {
+        latex: Some(LatexOptions {
+            exclude: Some(vec!["**/baz.tex".into()]),
+            ..LatexOptions::default()
+        }),
+        bibtex: None,
+    };
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+    scenario.open("foo.tex").await;
+
+    let params = WorkspaceSymbolParams {
+        query: String::new(),
+    };
+    let symbols = scenario
+        .server
+        .execute(|svr| svr.workspace_symbol(params))
+        .await
+        .unwrap();
+
+    assert!(symbols.iter().any(|symbol| symbol.name.contains("Bar")));
+    assert!(symbols.iter().all(|symbol| !symbol.name.contains("Baz")));
+}
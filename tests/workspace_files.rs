@@ -0,0 +1,66 @@
+use std::fs;
+use texlab_protocol::*;
+use texlab_test::{Scenario, CLIENT_FULL_CAPABILITIES};
+
+async fn label_names(scenario: &Scenario) -> Vec<String> {
+    let params = CompletionParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier::new(scenario.uri("foo.tex").into()),
+            position: Position::new(1, 5),
+        },
+        context: None,
+    };
+    scenario
+        .server
+        .execute(|svr| svr.completion(params))
+        .await
+        .unwrap()
+        .items
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+}
+
+#[tokio::test]
+async fn delete_file() {
+    let scenario = Scenario::new("workspace/files", false).await;
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+    scenario.open("foo.tex").await;
+    assert!(label_names(&scenario).await.contains(&"sec:bar".to_owned()));
+
+    fs::remove_file(scenario.directory.path().join("bar.tex")).unwrap();
+    let params = DidChangeWatchedFilesParams {
+        changes: vec![FileEvent {
+            uri: scenario.uri("bar.tex").into(),
+            typ: FileChangeType::Deleted,
+        }],
+    };
+    scenario
+        .server
+        .execute(|svr| svr.did_change_watched_files(params))
+        .await;
+
+    assert!(!label_names(&scenario).await.contains(&"sec:bar".to_owned()));
+}
+
+#[tokio::test]
+async fn change_open_file() {
+    let scenario = Scenario::new("workspace/files", false).await;
+    scenario.initialize(&CLIENT_FULL_CAPABILITIES).await;
+    scenario.open("foo.tex").await;
+
+    let path = scenario.directory.path().join("foo.tex");
+    fs::write(&path, "\\input{bar}\n\\ref{}\n\\label{sec:foo}").unwrap();
+    let params = DidChangeWatchedFilesParams {
+        changes: vec![FileEvent {
+            uri: scenario.uri("foo.tex").into(),
+            typ: FileChangeType::Changed,
+        }],
+    };
+    scenario
+        .server
+        .execute(|svr| svr.did_change_watched_files(params))
+        .await;
+
+    assert!(!label_names(&scenario).await.contains(&"sec:foo".to_owned()));
+}
@@ -0,0 +1,82 @@
+use texlab_protocol::*;
+use texlab_test::{Scenario, CLIENT_FULL_CAPABILITIES};
+
+async fn symbol_names(scenario: &Scenario) -> Vec<String> {
+    let params = WorkspaceSymbolParams {
+        query: String::new(),
+    };
+    scenario
+        .server
+        .execute(|svr| svr.workspace_symbol(params))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|symbol| symbol.name)
+        .collect()
+}
+
+async fn change_folders(scenario: &Scenario, added: &[&str], removed: &[&str]) {
+    let params = DidChangeWorkspaceFoldersParams {
+        event: WorkspaceFoldersChangeEvent {
+            added: added.iter().map(|name| scenario.folder(name)).collect(),
+            removed: removed.iter().map(|name| scenario.folder(name)).collect(),
+        },
+    };
+    scenario
+        .server
+        .execute(|svr| svr.did_change_workspace_folders(params))
+        .await;
+}
+
+#[tokio::test]
+async fn add_folder() {
+    let scenario = Scenario::new("workspace/folders", false).await;
+    scenario
+        .initialize_folders(&CLIENT_FULL_CAPABILITIES, Some(&["foo"][..]))
+        .await;
+    scenario.open("foo/foo.tex").await;
+    let names = symbol_names(&scenario).await;
+    assert!(names.iter().all(|name| !name.contains("Bar")));
+
+    change_folders(&scenario, &["bar"], &[]).await;
+    let names = symbol_names(&scenario).await;
+    assert!(names.iter().any(|name| name.contains("Foo")));
+    assert!(names.iter().any(|name| name.contains("Bar")));
+}
+
+#[tokio::test]
+async fn remove_folder() {
+    let scenario = Scenario::new("workspace/folders", false).await;
+    scenario
+        .initialize_folders(&CLIENT_FULL_CAPABILITIES, Some(&["foo"][..]))
+        .await;
+    scenario.open("foo/foo.tex").await;
+    change_folders(&scenario, &["bar"], &[]).await;
+    change_folders(&scenario, &[], &["bar"]).await;
+    let names = symbol_names(&scenario).await;
+    assert!(names.iter().any(|name| name.contains("Foo")));
+    assert!(names.iter().all(|name| !name.contains("Bar")));
+}
+
+#[tokio::test]
+async fn initialize_folders() {
+    let scenario = Scenario::new("workspace/folders", false).await;
+    scenario
+        .initialize_folders(&CLIENT_FULL_CAPABILITIES, Some(&["foo", "bar"][..]))
+        .await;
+    let names = symbol_names(&scenario).await;
+    assert!(names.iter().any(|name| name.contains("Foo")));
+    assert!(names.iter().any(|name| name.contains("Bar")));
+}
+
+#[tokio::test]
+async fn remove_folder_open_document() {
+    let scenario = Scenario::new("workspace/folders", false).await;
+    scenario
+        .initialize_folders(&CLIENT_FULL_CAPABILITIES, Some(&["foo", "bar"][..]))
+        .await;
+    scenario.open("bar/bar.tex").await;
+    change_folders(&scenario, &[], &["bar"]).await;
+    let names = symbol_names(&scenario).await;
+    assert!(names.iter().any(|name| name.contains("Bar")));
+}